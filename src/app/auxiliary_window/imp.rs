@@ -0,0 +1,260 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    sync::OnceLock,
+    time::Duration,
+};
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{
+    gio,
+    glib::{
+        self, ControlFlow, Propagation, SourceId, clone,
+        subclass::{InitializingObject, Signal},
+    },
+};
+use tracing::error;
+use url::Url;
+
+use crate::{
+    app::{bindings, context_menu, webview::WebView},
+    chromium::{AuxiliaryBrowser, ChromiumEvent},
+};
+
+#[derive(Default, gtk::CompositeTemplate)]
+#[template(file = "auxiliary_window.ui")]
+pub struct AuxiliaryWindow {
+    #[template_child]
+    pub toolbar_view: TemplateChild<adw::ToolbarView>,
+    #[template_child]
+    pub window_title: TemplateChild<adw::WindowTitle>,
+    pub webview: WebView,
+    pub browser: Rc<RefCell<Option<AuxiliaryBrowser>>>,
+    /// Last address the browser navigated to
+    pub address: RefCell<String>,
+    /// Set when the page closed the browser itself
+    pub closed: Cell<bool>,
+    pub events: RefCell<Option<SourceId>>,
+}
+
+impl AuxiliaryWindow {
+    pub fn set_browser(&self, browser: AuxiliaryBrowser) {
+        let object = self.obj();
+
+        self.webview.set_scale_factor(browser.scale_factor());
+        self.browser.replace(Some(browser));
+
+        let browser = self.browser.clone();
+        let source = glib::timeout_add_local(
+            Duration::from_millis(5),
+            clone!(
+                #[weak]
+                object,
+                #[upgrade_or]
+                ControlFlow::Break,
+                move || {
+                    let imp = object.imp();
+
+                    if let Some(ref browser) = *browser.borrow() {
+                        browser.on_event(|event| imp.handle_event(browser, event));
+                    }
+
+                    // Closing the window needs the browser, so wait until all events were handled
+                    if imp.closed.get() {
+                        imp.events.take();
+                        object.close();
+                        return ControlFlow::Break;
+                    }
+
+                    ControlFlow::Continue
+                }
+            ),
+        );
+
+        self.events.replace(Some(source));
+
+        if self.obj().is_realized() {
+            self.update_monitor_info();
+        }
+    }
+
+    /// Renders at the rate and scale of the monitor this window is on, not the main window's
+    fn update_monitor_info(&self) {
+        let object = self.obj();
+        let Some(surface) = object.surface() else {
+            return;
+        };
+
+        if let Some(monitor) = object.display().monitor_at_surface(&surface) {
+            let refresh_rate = monitor.refresh_rate() as f64 / 1000.0;
+            let scale_factor = object.scale_factor();
+
+            self.webview.set_scale_factor(scale_factor);
+
+            if let Some(ref browser) = *self.browser.borrow() {
+                browser.set_monitor_info(refresh_rate, scale_factor);
+            }
+        }
+    }
+
+    fn open_uri(&self, uri: &str) {
+        gtk::UriLauncher::new(uri).launch(Some(&*self.obj()), gio::Cancellable::NONE, |result| {
+            if let Err(e) = result {
+                error!("Failed to open uri: {e}");
+            }
+        });
+    }
+
+    fn handle_event(&self, browser: &AuxiliaryBrowser, event: ChromiumEvent) {
+        match event {
+            ChromiumEvent::Render(frame) => self.webview.render(frame),
+            ChromiumEvent::Closed => self.closed.set(true),
+            ChromiumEvent::Fullscreen(state) => self.obj().set_fullscreened(state),
+            ChromiumEvent::Title(title) => self.window_title.set_title(&title),
+            ChromiumEvent::Address(address) => {
+                // The host is all that tells which site is asking for credentials
+                let host = Url::parse(&address)
+                    .ok()
+                    .and_then(|url| url.host_str().map(str::to_owned))
+                    .unwrap_or_default();
+
+                self.window_title.set_subtitle(&host);
+                self.address.replace(address.clone());
+                self.obj()
+                    .emit_by_name::<()>("browser-navigated", &[&address]);
+            }
+            ChromiumEvent::Cursor(cursor) => self.webview.set_browser_cursor(cursor),
+            ChromiumEvent::TextSelection(text) => self.webview.set_selection(text),
+            ChromiumEvent::ImeBounds(x, y, width, height) => {
+                self.webview.set_ime_cursor_location(x, y, width, height)
+            }
+            // Logins opening popups of their own continue in this window
+            ChromiumEvent::OpenAuxiliary(url) => browser.load_url(url.as_str()),
+            ChromiumEvent::Open(url) => self.open_uri(url.as_str()),
+            ChromiumEvent::Console(message) => message.log(),
+            // Nothing a login needs, answer right away instead of leaving the page waiting
+            ChromiumEvent::FileDialog(dialog) => dialog.cancel(),
+            ChromiumEvent::PermissionRequest(request) => request.deny(),
+            ChromiumEvent::CertificateError(error) => error.deny(),
+            // Already logged, the main window reports it for its own requests
+            ChromiumEvent::ProxyAuthFailed(_) => {}
+            // Dropping the request without a path cancels the download
+            ChromiumEvent::DownloadRequest(request) => drop(request),
+            // Never reported, as every download was cancelled above
+            ChromiumEvent::Download(_) => {}
+            // Without the items of the main page, only the built-in entries
+            ChromiumEvent::ContextMenu(context_menu) => {
+                if let Some(model) = context_menu::menu_model(&context_menu, &[], false) {
+                    self.webview
+                        .popup_menu(&model, context_menu.x, context_menu.y);
+                }
+            }
+            // Only meant for the main page, which talks to the login through its events
+            ChromiumEvent::Ready
+            | ChromiumEvent::Loaded
+            | ChromiumEvent::Deeplink(_)
+            | ChromiumEvent::Ipc(_)
+            | ChromiumEvent::Status(_) => {}
+        }
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for AuxiliaryWindow {
+    const NAME: &'static str = "AuxiliaryWindow";
+    type Type = super::AuxiliaryWindow;
+    type ParentType = adw::Window;
+
+    fn class_init(klass: &mut Self::Class) {
+        klass.bind_template();
+    }
+
+    fn instance_init(obj: &InitializingObject<Self>) {
+        obj.init_template();
+    }
+}
+
+impl ObjectImpl for AuxiliaryWindow {
+    fn signals() -> &'static [Signal] {
+        static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+        SIGNALS.get_or_init(|| {
+            vec![
+                Signal::builder("browser-navigated")
+                    .param_types([String::static_type()])
+                    .build(),
+                Signal::builder("browser-closed")
+                    .param_types([String::static_type()])
+                    .build(),
+            ]
+        })
+    }
+
+    fn constructed(&self) {
+        self.parent_constructed();
+
+        let object = self.obj();
+        let webview = &self.webview;
+        self.toolbar_view.set_content(Some(webview));
+
+        object.connect_realize(|object| object.imp().update_monitor_info());
+        object.connect_notify_local(Some("scale-factor"), |object, _| {
+            object.imp().update_monitor_info()
+        });
+
+        let browser = self.browser.clone();
+        object.connect_visible_notify(move |object| {
+            if let Some(ref browser) = *browser.borrow() {
+                browser.hidden(!object.is_visible());
+            }
+        });
+
+        bindings::bind_browser(
+            webview,
+            &self.browser,
+            // Only the main browser navigates with the mouse buttons
+            || false,
+            clone!(
+                #[weak]
+                object,
+                move |url| object.imp().open_uri(url.as_str())
+            ),
+        );
+    }
+
+    fn dispose(&self) {
+        // Destroyed along with the main window, without a close request
+        if let Some(browser) = self.browser.take() {
+            browser.close();
+        }
+    }
+}
+
+impl WidgetImpl for AuxiliaryWindow {
+    fn map(&self) {
+        self.parent_map();
+
+        self.webview.grab_focus();
+    }
+}
+
+impl WindowImpl for AuxiliaryWindow {
+    fn close_request(&self) -> Propagation {
+        if let Some(source) = self.events.take() {
+            source.remove();
+        }
+
+        if let Some(browser) = self.browser.take() {
+            if !self.closed.get() {
+                browser.close();
+            }
+
+            let url = self.address.take();
+            self.obj().emit_by_name::<()>("browser-closed", &[&url]);
+        }
+
+        self.parent_close_request()
+    }
+}
+
+impl AdwWindowImpl for AuxiliaryWindow {}
@@ -0,0 +1,62 @@
+mod imp;
+
+use adw::subclass::prelude::*;
+use gtk::{
+    glib::{self, object::IsA},
+    prelude::*,
+};
+
+use crate::{chromium::AuxiliaryBrowser, settings::InputSettings};
+
+glib::wrapper! {
+    /// Separate window for popups a page opens, such as logins of other sites.
+    ///
+    /// The page that opened it gets no `window.opener` and can't `postMessage` to it. It is
+    /// told about the addresses the window navigates to and the last one when it closes,
+    /// which is where logins redirect to with their result.
+    pub struct AuxiliaryWindow(ObjectSubclass<imp::AuxiliaryWindow>)
+    @extends adw::Window, gtk::Window, gtk::Widget,
+    @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::ShortcutManager, gtk::Native, gtk::Root;
+}
+
+impl AuxiliaryWindow {
+    pub fn new(parent: &impl IsA<gtk::Window>, input: &InputSettings) -> Self {
+        let window: Self = glib::Object::builder()
+            .property("transient-for", parent.upcast_ref::<gtk::Window>())
+            .property("destroy-with-parent", true)
+            .build();
+
+        let webview = &window.imp().webview;
+        webview.set_wheel_multiplier(input.wheel_multiplier);
+        webview.set_kinetic_scrolling(input.kinetic_scrolling);
+
+        window
+    }
+
+    /// Shows the browser in the window, until either of them closes
+    pub fn set_browser(&self, browser: AuxiliaryBrowser) {
+        self.imp().set_browser(browser);
+    }
+
+    /// Called whenever the browser navigates, with the new address
+    pub fn connect_browser_navigated<T: Fn(String) + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "browser-navigated",
+            false,
+            glib::closure_local!(move |_: AuxiliaryWindow, url: String| {
+                callback(url);
+            }),
+        );
+    }
+
+    /// Called once the window and its browser are gone, with the last address shown
+    pub fn connect_browser_closed<T: Fn(String) + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "browser-closed",
+            false,
+            glib::closure_local!(move |_: AuxiliaryWindow, url: String| {
+                callback(url);
+            }),
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use std::{cell::RefCell, ops::Deref, rc::Rc};
+
+use gtk::{
+    gio,
+    glib::{self, clone},
+    prelude::*,
+};
+use url::Url;
+
+use crate::{
+    app::{
+        config::{BACK_BUTTON, FORWARD_BUTTON},
+        webview::WebView,
+    },
+    chromium::BrowserView,
+    shared::{states::TouchPhase, types::ClipboardAction},
+};
+
+/// Forwards the input of a webview to the browser it shows, and adds the context menu
+/// actions every browser has to the returned group
+pub fn bind_browser<B: Deref<Target = BrowserView> + 'static>(
+    webview: &WebView,
+    browser: &Rc<RefCell<Option<B>>>,
+    navigation_buttons: impl Fn() -> bool + 'static,
+    open_uri: impl Fn(Url) + 'static,
+) -> gio::SimpleActionGroup {
+    let context_actions = gio::SimpleActionGroup::new();
+
+    let browser_ref = browser.clone();
+    let copy_action = gio::SimpleAction::new("copy", None);
+    copy_action.connect_activate(clone!(
+        #[weak]
+        webview,
+        move |_, _| {
+            if let Some(ref browser) = *browser_ref.borrow() {
+                webview.copy_selection();
+                browser.clipboard(ClipboardAction::Copy);
+            }
+        }
+    ));
+    context_actions.add_action(&copy_action);
+
+    let browser_ref = browser.clone();
+    let paste_action = gio::SimpleAction::new("paste", None);
+    paste_action.connect_activate(clone!(
+        #[weak]
+        webview,
+        move |_, _| {
+            let browser = browser_ref.clone();
+            webview.read_clipboard(move |text| {
+                if let Some(ref browser) = *browser.borrow() {
+                    clipboard(browser, ClipboardAction::Paste, text);
+                }
+            });
+        }
+    ));
+    context_actions.add_action(&paste_action);
+
+    let copy_link_action = gio::SimpleAction::new("copy-link", Some(glib::VariantTy::STRING));
+    copy_link_action.connect_activate(clone!(
+        #[weak]
+        webview,
+        move |_, parameter| {
+            if let Some(link) = parameter.and_then(|link| link.get::<String>()) {
+                webview.display().clipboard().set_text(&link);
+            }
+        }
+    ));
+    context_actions.add_action(&copy_link_action);
+
+    let open_link_action = gio::SimpleAction::new("open-link", Some(glib::VariantTy::STRING));
+    open_link_action.connect_activate(move |_, parameter| {
+        if let Some(link) = parameter.and_then(|link| link.get::<String>())
+            && let Ok(url) = Url::parse(&link)
+        {
+            open_uri(url);
+        }
+    });
+    context_actions.add_action(&open_link_action);
+
+    webview.insert_action_group("context", Some(&context_actions));
+
+    let browser_ref = browser.clone();
+    webview.connect_has_focus_notify(move |_| {
+        if let Some(ref browser) = *browser_ref.borrow() {
+            browser.focus(true);
+        }
+    });
+
+    let browser_ref = browser.clone();
+    webview.connect_resized(move |width, height| {
+        if let Some(ref browser) = *browser_ref.borrow() {
+            browser.resize(width, height);
+        }
+    });
+
+    let browser_ref = browser.clone();
+    webview.connect_motion(move |pointer_state| {
+        if let Some(ref browser) = *browser_ref.borrow() {
+            browser.forward_motion(&pointer_state);
+        }
+    });
+
+    let browser_ref = browser.clone();
+    webview.connect_scroll(move |pointer_state, delta_x, delta_y| {
+        if let Some(ref browser) = *browser_ref.borrow() {
+            browser.forward_scroll(&pointer_state, delta_x, delta_y);
+        }
+    });
+
+    let browser_ref = browser.clone();
+    webview.connect_click(clone!(
+        #[weak]
+        webview,
+        move |pointer_state, count| {
+            if let Some(ref browser) = *browser_ref.borrow() {
+                webview.grab_focus();
+
+                let button = pointer_state.button();
+                if button != BACK_BUTTON && button != FORWARD_BUTTON {
+                    browser.forward_click(&pointer_state, count);
+                    return;
+                }
+
+                // CEF has no back and forward button types, navigate once per press
+                if navigation_buttons() && pointer_state.pressed() {
+                    if button == BACK_BUTTON {
+                        browser.go_back();
+                    } else {
+                        browser.go_forward();
+                    }
+                }
+            }
+        }
+    ));
+
+    let browser_ref = browser.clone();
+    webview.connect_touch(clone!(
+        #[weak]
+        webview,
+        move |touch_point| {
+            if let Some(ref browser) = *browser_ref.borrow() {
+                if matches!(touch_point.phase, TouchPhase::Pressed) {
+                    webview.grab_focus();
+                }
+
+                browser.forward_touch(&touch_point);
+            }
+        }
+    ));
+
+    let browser_ref = browser.clone();
+    webview.connect_pinch(move |factor| {
+        if let Some(ref browser) = *browser_ref.borrow() {
+            browser.zoom_by(factor);
+        }
+    });
+
+    let browser_ref = browser.clone();
+    webview.connect_swipe(move |back| {
+        if let Some(ref browser) = *browser_ref.borrow() {
+            if back {
+                browser.go_back();
+            } else {
+                browser.go_forward();
+            }
+        }
+    });
+
+    let browser_ref = browser.clone();
+    webview.connect_keys(clone!(
+        #[weak]
+        webview,
+        move |keyboard_state| {
+            if let Some(ref browser) = *browser_ref.borrow() {
+                webview.grab_focus();
+                browser.forward_key(&keyboard_state);
+            }
+        }
+    ));
+
+    let browser_ref = browser.clone();
+    webview.connect_ime_preedit(move |text, cursor| {
+        if let Some(ref browser) = *browser_ref.borrow() {
+            browser.ime_set_composition(&text, cursor);
+        }
+    });
+
+    let browser_ref = browser.clone();
+    webview.connect_ime_commit(move |text| {
+        if let Some(ref browser) = *browser_ref.borrow() {
+            browser.ime_commit_text(&text);
+        }
+    });
+
+    let browser_ref = browser.clone();
+    webview.connect_clipboard(move |action, text| {
+        if let Some(ref browser) = *browser_ref.borrow() {
+            clipboard(browser, action, text);
+        }
+    });
+
+    let browser_ref = browser.clone();
+    webview.connect_primary_paste(move |pointer_state, text| {
+        if let Some(ref browser) = *browser_ref.borrow() {
+            browser.paste_primary(&pointer_state, &text);
+        }
+    });
+
+    let browser_ref = browser.clone();
+    webview.connect_file_enter(move |pointer_state, path| {
+        if let Some(ref browser) = *browser_ref.borrow() {
+            browser.forward_file_enter(pointer_state.as_ref(), path);
+        }
+    });
+
+    let browser_ref = browser.clone();
+    webview.connect_file_leave(move || {
+        if let Some(ref browser) = *browser_ref.borrow() {
+            browser.forward_file_leave();
+        }
+    });
+
+    let browser_ref = browser.clone();
+    webview.connect_file_motion(move |pointer_state| {
+        if let Some(ref browser) = *browser_ref.borrow() {
+            browser.forward_file_hover(pointer_state.as_ref());
+        }
+    });
+
+    let browser_ref = browser.clone();
+    webview.connect_file_drop(move |pointer_state| {
+        if let Some(ref browser) = *browser_ref.borrow() {
+            browser.forward_file_drop(pointer_state.as_ref());
+        }
+    });
+
+    context_actions
+}
+
+/// Pastes the text read from the system clipboard, anything else is left to the browser,
+/// such as pasting images it can reach itself
+fn clipboard(browser: &BrowserView, action: ClipboardAction, text: Option<String>) {
+    match text {
+        Some(text) => browser.ime_commit_text(&text),
+        None => browser.clipboard(action),
+    }
+}
@@ -5,3 +5,7 @@ pub const APP_ID: &str = match cfg!(debug_assertions) {
 pub const APP_NAME: &str = "Stremio";
 
 pub const URI_SCHEME: &str = "stremio://";
+
+/// Extra mouse buttons, usually on the side of the mouse
+pub const BACK_BUTTON: u32 = 8;
+pub const FORWARD_BUTTON: u32 = 9;
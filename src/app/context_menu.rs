@@ -0,0 +1,60 @@
+use gettextrs::gettext;
+use gtk::{gio, prelude::*};
+
+use crate::{chromium::ContextMenu, shared::ipc::event::ContextMenuItem};
+
+/// Builds the menu for what was right clicked, `None` when there is nothing to offer
+pub fn menu_model(
+    context_menu: &ContextMenu,
+    items: &[ContextMenuItem],
+    dev_mode: bool,
+) -> Option<gio::Menu> {
+    let menu = gio::Menu::new();
+
+    if let Some(link) = &context_menu.link {
+        let section = gio::Menu::new();
+        section.append_item(&item(
+            &gettext("_Open Link in Browser"),
+            "context.open-link",
+            link,
+        ));
+        section.append_item(&item(&gettext("Copy _Link"), "context.copy-link", link));
+        menu.append_section(None, &section);
+    }
+
+    let section = gio::Menu::new();
+    if !context_menu.selection.is_empty() {
+        section.append(Some(&gettext("_Copy")), Some("context.copy"));
+    }
+    if context_menu.editable {
+        section.append(Some(&gettext("_Paste")), Some("context.paste"));
+    }
+    append_section(&menu, &section);
+
+    let section = gio::Menu::new();
+    for custom in items {
+        section.append_item(&item(&custom.label, "context.custom", &custom.id));
+    }
+    append_section(&menu, &section);
+
+    if dev_mode {
+        let inspect = gio::MenuItem::new(Some(&gettext("_Inspect")), None);
+        let position = (context_menu.x, context_menu.y).to_variant();
+        inspect.set_action_and_target_value(Some("context.inspect"), Some(&position));
+        menu.append_item(&inspect);
+    }
+
+    (menu.n_items() > 0).then_some(menu)
+}
+
+fn append_section(menu: &gio::Menu, section: &gio::Menu) {
+    if section.n_items() > 0 {
+        menu.append_section(None, section);
+    }
+}
+
+fn item(label: &str, action: &str, target: &str) -> gio::MenuItem {
+    let item = gio::MenuItem::new(Some(label), None);
+    item.set_action_and_target_value(Some(action), Some(&target.to_variant()));
+    item
+}
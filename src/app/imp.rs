@@ -5,22 +5,27 @@ use std::{
 
 use adw::{prelude::*, subclass::prelude::*};
 
-use gtk::glib::{self, ControlFlow, Properties, clone};
+use gtk::{
+    gio,
+    glib::{self, ControlFlow, Properties, clone},
+};
 
 use crate::{
     app::{
-        config::URI_SCHEME, settings_window::SettingsWindow, tray::Tray, video::Video,
-        webview::WebView, window::Window,
+        auxiliary_window::AuxiliaryWindow, bindings, config::URI_SCHEME, context_menu,
+        settings_window::SettingsWindow, tray::Tray, video::Video, webview::WebView,
+        window::Window,
     },
     chromium::{Chromium, ChromiumEvent},
-    discord::adapter::DiscordAdapter,
+    discord::adapter::{DiscordAdapter, DiscordMedia},
     mpris::{adapter::MprisAdapter, metadata},
+    settings::Settings,
     shared::{
         ipc::{
             self,
-            event::{IpcEvent, IpcEventMpv},
+            event::{ContextMenuItem, IpcEvent, IpcEventMpv},
         },
-        types::{MprisCommand, SCALE_FACTOR, UserEvent},
+        types::{MprisCommand, PlaybackStatus, SCALE_FACTOR, UserEvent},
     },
 };
 
@@ -40,12 +45,19 @@ pub struct Application {
     deeplink: Rc<RefCell<Option<String>>>,
     mpris_adapter: Rc<RefCell<Option<MprisAdapter>>>,
     discord_adapter: Rc<RefCell<Option<DiscordAdapter>>>,
+    settings: Rc<RefCell<Settings>>,
+    context_menu_items: Rc<RefCell<Vec<ContextMenuItem>>>,
+    webview: glib::WeakRef<WebView>,
 }
 
 impl Application {
     pub fn set_browser(&self, browser: Chromium) {
         *self.browser.borrow_mut() = Some(browser);
     }
+
+    pub fn set_settings(&self, settings: Settings) {
+        *self.settings.borrow_mut() = settings;
+    }
 }
 
 #[glib::object_subclass]
@@ -86,6 +98,10 @@ impl ApplicationImpl for Application {
                         .map(|d| d.is_connected())
                         .unwrap_or(false);
                     window.set_discord_active(discord_active);
+                    window.set_window_settings(&app.imp().settings.borrow().window);
+                    window.set_input_settings(&app.imp().settings.borrow().input);
+                    window.set_proxy_settings(&app.imp().settings.borrow().proxy);
+                    window.set_discord_settings(&app.imp().settings.borrow().discord);
 
                     window.present(Some(&main_window));
 
@@ -100,11 +116,62 @@ impl ApplicationImpl for Application {
                         }),
                     );
 
+                    let settings = app.imp().settings.clone();
+                    window.connect_closure(
+                        "window-settings-changed",
+                        false,
+                        glib::closure_local!(
+                            #[weak]
+                            main_window,
+                            move |window: SettingsWindow| {
+                                let mut settings = settings.borrow_mut();
+                                window.update_window_settings(&mut settings.window);
+                                settings.save();
+
+                                main_window.set_close_action(settings.window.close_action);
+                            }
+                        ),
+                    );
+
+                    let settings = app.imp().settings.clone();
+                    let webview = app.imp().webview.clone();
+                    window.connect_closure(
+                        "input-settings-changed",
+                        false,
+                        glib::closure_local!(move |window: SettingsWindow| {
+                            let mut settings = settings.borrow_mut();
+                            window.update_input_settings(&mut settings.input);
+                            settings.save();
+
+                            if let Some(webview) = webview.upgrade() {
+                                webview.set_wheel_multiplier(settings.input.wheel_multiplier);
+                                webview.set_kinetic_scrolling(settings.input.kinetic_scrolling);
+                            }
+                        }),
+                    );
+
+                    let settings = app.imp().settings.clone();
+                    window.connect_closure(
+                        "proxy-settings-changed",
+                        false,
+                        glib::closure_local!(move |window: SettingsWindow| {
+                            let mut settings = settings.borrow_mut();
+                            window.update_proxy_settings(&mut settings.proxy);
+                            settings.save();
+                        }),
+                    );
+
+                    let settings = app.imp().settings.clone();
                     let discord_adapter = app.imp().discord_adapter.clone();
+                    let startup_url = app.imp().startup_url.borrow().clone();
                     window.connect_closure(
                         "discord-toggled",
                         false,
                         glib::closure_local!(move |_: SettingsWindow, active: bool| {
+                            let mut settings = settings.borrow_mut();
+                            settings.discord.enabled = active;
+                            settings.save();
+
                             let mut adapter_lock = discord_adapter.borrow_mut();
                             if let Some(adapter) = adapter_lock.as_mut() {
                                 if active {
@@ -113,8 +180,8 @@ impl ApplicationImpl for Application {
                                     let _ = adapter.disconnect();
                                 }
                             } else if active {
-                                // Lazy init if not exists? (Should exist though)
-                                if let Ok(mut adapter) = DiscordAdapter::new("1450906751607111781")
+                                if let Ok(mut adapter) =
+                                    DiscordAdapter::new(&settings.discord, &startup_url)
                                 {
                                     let _ = adapter.connect();
                                     *adapter_lock = Some(adapter);
@@ -122,6 +189,37 @@ impl ApplicationImpl for Application {
                             }
                         }),
                     );
+
+                    let settings = app.imp().settings.clone();
+                    let discord_adapter = app.imp().discord_adapter.clone();
+                    let startup_url = app.imp().startup_url.borrow().clone();
+                    window.connect_closure(
+                        "discord-settings-changed",
+                        false,
+                        glib::closure_local!(move |window: SettingsWindow| {
+                            let mut settings = settings.borrow_mut();
+                            window.update_discord_settings(&mut settings.discord);
+                            settings.save();
+
+                            let mut adapter_lock = discord_adapter.borrow_mut();
+                            let client_changed = adapter_lock
+                                .as_ref()
+                                .is_some_and(|d| d.client_id() != settings.discord.client_id);
+
+                            if client_changed {
+                                // The client id is bound to the IPC connection, start over
+                                *adapter_lock =
+                                    DiscordAdapter::new(&settings.discord, &startup_url).ok();
+                                if let Some(adapter) = adapter_lock.as_mut()
+                                    && settings.discord.enabled
+                                {
+                                    let _ = adapter.connect();
+                                }
+                            } else if let Some(adapter) = adapter_lock.as_mut() {
+                                let _ = adapter.set_settings(&settings.discord);
+                            }
+                        }),
+                    );
                 }
             }
         ));
@@ -146,25 +244,159 @@ impl ApplicationImpl for Application {
         let tray = Tray::default();
         let video = Video::default();
         let webview = WebView::default();
+        webview.set_wheel_multiplier(self.settings.borrow().input.wheel_multiplier);
+        webview.set_kinetic_scrolling(self.settings.borrow().input.kinetic_scrolling);
+        self.webview.set(Some(&webview));
         let window = Window::new(&app);
         window.set_property("decorations", self.decorations.get());
+        window.set_close_action(self.settings.borrow().window.close_action);
+        window.restore_state(&self.settings.borrow().window);
         window.set_underlay(&video);
         window.set_overlay(&webview);
 
+        let play_pause_action = gtk::gio::SimpleAction::new("play-pause", None);
+        play_pause_action.connect_activate(clone!(
+            #[weak]
+            video,
+            move |_, _| {
+                video.send_command("cycle".into(), vec!["pause".into()]);
+            }
+        ));
+        app.add_action(&play_pause_action);
+
+        let browser = self.browser.clone();
+        let mini_player_action =
+            gtk::gio::SimpleAction::new_stateful("mini-player", None, &false.to_variant());
+        mini_player_action.connect_change_state(clone!(
+            #[weak]
+            window,
+            #[weak]
+            tray,
+            move |action, state| {
+                if let Some(active) = state.and_then(|state| state.get::<bool>()) {
+                    // Leaving fullscreen for the mini player has to be reflected in the web UI
+                    if active
+                        && window.is_fullscreen()
+                        && let Some(ref browser) = *browser.borrow()
+                    {
+                        let message = ipc::create_response(IpcEvent::Fullscreen(false));
+                        browser.post_message(message);
+                    }
+
+                    window.set_mini_player(active);
+                    tray.update_mini_player(active);
+                    action.set_state(&active.to_variant());
+                }
+            }
+        ));
+        app.add_action(&mini_player_action);
+        app.set_accels_for_action("app.mini-player", &["<Control><Shift>m"]);
+
+        let fit_to_video_action =
+            gtk::gio::SimpleAction::new("fit-to-video", Some(glib::VariantTy::INT32));
+        fit_to_video_action.connect_activate(clone!(
+            #[weak]
+            window,
+            move |_, parameter| {
+                if let Some(percentage) = parameter.and_then(|parameter| parameter.get::<i32>()) {
+                    window.fit_to_video(percentage);
+                }
+            }
+        ));
+        // Only enabled during playback, so its shortcuts reach the page otherwise
+        fit_to_video_action.set_enabled(false);
+        app.add_action(&fit_to_video_action);
+        app.set_accels_for_action("app.fit-to-video(50)", &["<Control><Shift>1"]);
+        app.set_accels_for_action("app.fit-to-video(100)", &["<Control><Shift>2"]);
+        app.set_accels_for_action("app.fit-to-video(200)", &["<Control><Shift>3"]);
+
+        let lock_aspect_ratio = self.settings.borrow().window.lock_aspect_ratio;
+        window.set_aspect_locked(lock_aspect_ratio);
+
+        let settings = self.settings.clone();
+        let lock_aspect_ratio_action = gtk::gio::SimpleAction::new_stateful(
+            "lock-aspect-ratio",
+            None,
+            &lock_aspect_ratio.to_variant(),
+        );
+        lock_aspect_ratio_action.connect_change_state(clone!(
+            #[weak]
+            window,
+            move |action, state| {
+                if let Some(locked) = state.and_then(|state| state.get::<bool>()) {
+                    window.set_aspect_locked(locked);
+                    action.set_state(&locked.to_variant());
+
+                    let mut settings = settings.borrow_mut();
+                    settings.window.lock_aspect_ratio = locked;
+                    settings.save();
+                }
+            }
+        ));
+        app.add_action(&lock_aspect_ratio_action);
+        app.set_accels_for_action("app.lock-aspect-ratio", &["<Control><Shift>a"]);
+
+        let settings = self.settings.clone();
+        let context_actions = bindings::bind_browser(
+            &webview,
+            &self.browser,
+            move || settings.borrow().input.navigation_buttons,
+            clone!(
+                #[weak]
+                window,
+                move |url| window.open_uri(url)
+            ),
+        );
+
+        let browser = self.browser.clone();
+        let inspect_action =
+            gio::SimpleAction::new("inspect", Some(&<(i32, i32)>::static_variant_type()));
+        inspect_action.connect_activate(move |_, parameter| {
+            if let Some(ref browser) = *browser.borrow()
+                && let Some((x, y)) = parameter.and_then(|position| position.get::<(i32, i32)>())
+            {
+                browser.inspect(x, y);
+            }
+        });
+        context_actions.add_action(&inspect_action);
+
+        let browser = self.browser.clone();
+        let custom_action = gio::SimpleAction::new("custom", Some(glib::VariantTy::STRING));
+        custom_action.connect_activate(move |_, parameter| {
+            if let Some(ref browser) = *browser.borrow()
+                && let Some(id) = parameter.and_then(|id| id.get::<String>())
+            {
+                let message = ipc::create_response(IpcEvent::ContextMenuAction(id));
+                browser.post_message(message);
+            }
+        });
+        context_actions.add_action(&custom_action);
+
         let (mpris_sender, mpris_receiver) = flume::unbounded::<UserEvent>();
         let adapter = MprisAdapter::new(mpris_sender.clone());
         *self.mpris_adapter.borrow_mut() = Some(adapter);
 
-        // Initialize Discord adapter (disconnected)
-        if let Ok(discord) = DiscordAdapter::new("1450906751607111781") {
+        // Initialize Discord adapter, connected only if enabled in settings
+        let discord_settings = self.settings.borrow().discord.clone();
+        let startup_url = self.startup_url.borrow().clone();
+        if let Ok(mut discord) = DiscordAdapter::new(&discord_settings, &startup_url) {
+            if discord_settings.enabled {
+                let _ = discord.connect();
+            }
+
             *self.discord_adapter.borrow_mut() = Some(discord);
         }
 
+        let browser = self.browser.clone();
         let mpris_adapter_ref = self.mpris_adapter.clone();
         let discord_adapter_ref = self.discord_adapter.clone();
         glib::MainContext::default().spawn_local(clone!(
             #[weak]
             video,
+            #[weak]
+            tray,
+            #[strong]
+            browser,
             #[strong]
             mpris_adapter_ref,
             #[strong]
@@ -180,6 +412,9 @@ impl ApplicationImpl for Application {
                                 poster,
                                 thumbnail,
                                 logo,
+                                id,
+                                kind,
+                                adult,
                             } => {
                                 adapter.update_metadata(
                                     title.clone(),
@@ -188,6 +423,7 @@ impl ApplicationImpl for Application {
                                     thumbnail,
                                     logo.clone(),
                                 );
+                                tray.update_now_playing(title.clone(), artist.clone());
                                 if let Some(discord) = discord_adapter_ref.borrow_mut().as_mut() {
                                     tracing::info!(
                                         "Discord RPC: updating activity for '{}' (Logo: {:?})",
@@ -195,11 +431,14 @@ impl ApplicationImpl for Application {
                                         logo
                                     );
                                     // User requested Logo instead of Poster
-                                    let _ = discord.update_activity(
-                                        title.as_deref(),
-                                        artist.as_deref(),
-                                        logo.as_deref(),
-                                    );
+                                    let _ = discord.update_activity(DiscordMedia {
+                                        title,
+                                        artist,
+                                        image: logo,
+                                        id,
+                                        kind,
+                                        adult,
+                                    });
                                 }
                             }
                             UserEvent::MprisCommand(cmd) => match cmd {
@@ -229,8 +468,29 @@ impl ApplicationImpl for Application {
                                     "set".into(),
                                     vec!["speed".into(), format!("{}", rate)],
                                 ),
+                                MprisCommand::SetVolume(volume) => video.send_command(
+                                    "set".into(),
+                                    vec!["volume".into(), format!("{}", volume * 100.0)],
+                                ),
+                                MprisCommand::AdjustVolume(offset) => video.send_command(
+                                    "add".into(),
+                                    vec!["volume".into(), format!("{}", offset)],
+                                ),
+                                MprisCommand::ToggleMute => {
+                                    video.send_command("cycle".into(), vec!["mute".into()])
+                                }
+                                MprisCommand::Next => {
+                                    if let Some(ref browser) = *browser.borrow() {
+                                        let message = ipc::create_response(IpcEvent::NextVideo);
+                                        browser.post_message(message);
+                                    }
+                                }
                                 _ => {}
                             },
+                            UserEvent::NextVideo(available) => {
+                                adapter.update_next_video(available);
+                                tray.update_next_video(available);
+                            }
                             _ => {}
                         }
                     }
@@ -272,16 +532,39 @@ impl ApplicationImpl for Application {
         video.connect_playback_started(clone!(
             #[weak]
             window,
+            #[weak]
+            tray,
+            #[weak]
+            fit_to_video_action,
             move || {
+                fit_to_video_action.set_enabled(true);
                 window.disable_idling();
+                tray.update_playback(PlaybackStatus::Playing);
             }
         ));
 
         video.connect_playback_ended(clone!(
+            #[weak]
+            app,
             #[weak]
             window,
+            #[weak]
+            tray,
+            #[weak]
+            fit_to_video_action,
             move || {
+                fit_to_video_action.set_enabled(false);
                 window.enable_idling();
+                window.set_video_size(None);
+                tray.clear_playback();
+
+                if let Some(ref adapter) = *app.imp().mpris_adapter.borrow() {
+                    adapter.update_playback_status("Stopped");
+                    adapter.update_next_video(false);
+                }
+
+                // There is nothing left to show in the mini player
+                app.change_action_state("mini-player", &false.to_variant());
             }
         ));
 
@@ -289,77 +572,129 @@ impl ApplicationImpl for Application {
         let mpris_adapter_ref = self.mpris_adapter.clone();
         let discord_adapter_ref = self.discord_adapter.clone();
         let mpris_sender_mpv = mpris_sender.clone();
-        video.connect_mpv_property_change(move |name, value| {
-            if let Some(ref browser) = *browser.borrow() {
-                let message = ipc::create_response(IpcEvent::Mpv(IpcEventMpv::Change((
-                    name.to_string(),
-                    value.clone(),
-                ))));
+        video.connect_mpv_property_change(clone!(
+            #[weak]
+            window,
+            #[weak]
+            tray,
+            move |name, value| {
+                if let Some(ref browser) = *browser.borrow() {
+                    let message = ipc::create_response(IpcEvent::Mpv(IpcEventMpv::Change((
+                        name.to_string(),
+                        value.clone(),
+                    ))));
 
-                browser.post_message(message);
-            }
+                    browser.post_message(message);
+                }
 
-            let mut adapter_lock = mpris_adapter_ref.borrow_mut();
-            if let Some(adapter) = adapter_lock.as_mut() {
                 match name {
                     "pause" => {
                         if let Some(paused) = value.as_bool() {
-                            adapter.update_playback_status(if paused {
-                                "Paused"
+                            window.set_paused(paused);
+                            tray.update_playback(if paused {
+                                PlaybackStatus::Paused
                             } else {
-                                "Playing"
+                                PlaybackStatus::Playing
                             });
                         }
                     }
-                    "media-title" => {
-                        if let Some(title) = value.as_str() {
-                            let clean_title = if title.starts_with("file://")
-                                || title.contains("&tr=")
-                                || title.contains("announce")
-                                || title.contains("dht:")
-                            {
-                                "Stremio".to_string()
-                            } else {
-                                title.to_string()
-                            };
-
-                            if !adapter.rich_metadata_active {
-                                adapter.update_metadata_simple(
-                                    Some(clean_title.clone()),
-                                    None,
-                                    None,
-                                    None,
-                                );
-                            }
-
-                            if let Some(discord) = discord_adapter_ref.borrow_mut().as_mut() {
-                                let _ = discord.update_activity(Some(&clean_title), None, None);
-                            }
-
-                            metadata::fetch_metadata(clean_title, mpris_sender_mpv.clone());
+                    "paused-for-cache" => {
+                        if let Some(buffering) = value.as_bool() {
+                            tray.update_buffering(buffering);
                         }
                     }
-                    "duration" => {
-                        if let Some(d) = value.as_f64() {
-                            adapter.update_metadata_simple(None, None, None, Some(d));
+                    "volume" => {
+                        if let Some(volume) = value.as_f64() {
+                            tray.update_volume(volume);
                         }
                     }
-                    "time-pos" => {
-                        if let Some(p) = value.as_f64() {
-                            adapter.update_position(p);
+                    "mute" => {
+                        if let Some(muted) = value.as_str() {
+                            tray.update_mute(muted == "yes");
                         }
                     }
-                    "sid" => {
-                        if let Some(sid) = value.as_str() {
-                            metadata::fetch_metadata_by_sid(
-                                sid.to_string(),
-                                mpris_sender_mpv.clone(),
-                            );
+                    "video-params" => {
+                        // Display size, with the pixel aspect ratio already applied
+                        let width = value.get("dw").and_then(|width| width.as_i64());
+                        let height = value.get("dh").and_then(|height| height.as_i64());
+
+                        if let (Some(width), Some(height)) = (width, height) {
+                            window.set_video_size(Some((width as i32, height as i32)));
                         }
                     }
                     _ => {}
                 }
+
+                let mut adapter_lock = mpris_adapter_ref.borrow_mut();
+                if let Some(adapter) = adapter_lock.as_mut() {
+                    match name {
+                        "pause" => {
+                            if let Some(paused) = value.as_bool() {
+                                adapter.update_playback_status(if paused {
+                                    "Paused"
+                                } else {
+                                    "Playing"
+                                });
+                            }
+                        }
+                        "media-title" => {
+                            if let Some(title) = value.as_str() {
+                                let clean_title = if title.starts_with("file://")
+                                    || title.contains("&tr=")
+                                    || title.contains("announce")
+                                    || title.contains("dht:")
+                                {
+                                    "Stremio".to_string()
+                                } else {
+                                    title.to_string()
+                                };
+
+                                if !adapter.rich_metadata_active {
+                                    adapter.update_metadata_simple(
+                                        Some(clean_title.clone()),
+                                        None,
+                                        None,
+                                        None,
+                                    );
+                                    tray.update_now_playing(Some(clean_title.clone()), None);
+                                }
+
+                                if let Some(discord) = discord_adapter_ref.borrow_mut().as_mut() {
+                                    let _ = discord.update_title(clean_title.clone());
+                                }
+
+                                metadata::fetch_metadata(clean_title, mpris_sender_mpv.clone());
+                            }
+                        }
+                        "duration" => {
+                            if let Some(d) = value.as_f64() {
+                                adapter.update_metadata_simple(None, None, None, Some(d));
+                            }
+                        }
+                        "time-pos" => {
+                            if let Some(p) = value.as_f64() {
+                                adapter.update_position(p);
+                            }
+                        }
+                        "sid" => {
+                            if let Some(sid) = value.as_str() {
+                                metadata::fetch_metadata_by_sid(
+                                    sid.to_string(),
+                                    mpris_sender_mpv.clone(),
+                                );
+                            }
+                        }
+                        _ => {}
+                    }
+                }
             }
+        ));
+
+        let mpris_sender_tray = mpris_sender.clone();
+        tray.connect_command(move |command| {
+            mpris_sender_tray
+                .send(UserEvent::MprisCommand(command))
+                .ok();
         });
 
         let browser = self.browser.clone();
@@ -367,6 +702,8 @@ impl ApplicationImpl for Application {
         let startup_url = self.startup_url.clone();
         let open_uri = self.open_uri.clone();
         let deeplink = self.deeplink.clone();
+        let context_menu_items = self.context_menu_items.clone();
+        let settings = self.settings.clone();
         glib::timeout_add_local(
             std::time::Duration::from_millis(5),
             clone!(
@@ -396,9 +733,110 @@ impl ApplicationImpl for Application {
                                     browser.post_message(message);
                                 }
                             }
+                            ChromiumEvent::Closed => tracing::debug!("Browser closed"),
                             ChromiumEvent::Fullscreen(state) => window.set_fullscreen(state),
                             ChromiumEvent::Render(frame) => webview.render(frame),
                             ChromiumEvent::Open(url) => window.open_uri(url),
+                            ChromiumEvent::OpenAuxiliary(url) => {
+                                let auxiliary_window =
+                                    AuxiliaryWindow::new(&window, &settings.borrow().input);
+                                let (width, height) = auxiliary_window.default_size();
+
+                                match browser.open_auxiliary(&url, width, height) {
+                                    Some(auxiliary) => {
+                                        auxiliary_window.set_browser(auxiliary);
+                                        auxiliary_window.connect_browser_navigated(clone!(
+                                            #[weak]
+                                            app,
+                                            move |url| {
+                                                if let Some(ref browser) =
+                                                    *app.imp().browser.borrow()
+                                                {
+                                                    let message = ipc::create_response(
+                                                        IpcEvent::AuxiliaryNavigated(url),
+                                                    );
+                                                    browser.post_message(message);
+                                                }
+                                            }
+                                        ));
+                                        auxiliary_window.connect_browser_closed(clone!(
+                                            #[weak]
+                                            app,
+                                            move |url| {
+                                                if let Some(ref browser) =
+                                                    *app.imp().browser.borrow()
+                                                {
+                                                    let message = ipc::create_response(
+                                                        IpcEvent::AuxiliaryClosed(url),
+                                                    );
+                                                    browser.post_message(message);
+                                                }
+                                            }
+                                        ));
+                                        auxiliary_window.present();
+                                    }
+                                    None => {
+                                        auxiliary_window.destroy();
+                                        window.open_uri(url);
+                                    }
+                                }
+                            }
+                            ChromiumEvent::Deeplink(uri) => {
+                                let message = ipc::create_response(IpcEvent::OpenMedia(uri));
+                                browser.post_message(message);
+                            }
+                            ChromiumEvent::TextSelection(text) => webview.set_selection(text),
+                            ChromiumEvent::Cursor(cursor) => webview.set_browser_cursor(cursor),
+                            ChromiumEvent::Title(title) => window.set_page_title(&title),
+                            ChromiumEvent::Address(url) => {
+                                tracing::debug!("Navigated to {url}");
+                                window.set_page_address(&url);
+                            }
+                            ChromiumEvent::Status(status) => window.set_status(&status),
+                            ChromiumEvent::Console(message) => message.log(),
+                            ChromiumEvent::FileDialog(dialog) => window.choose_files(dialog),
+                            ChromiumEvent::PermissionRequest(request) => {
+                                let decision = settings
+                                    .borrow()
+                                    .permission_decision(&request.origin, &request.permissions);
+
+                                match decision {
+                                    Some(true) => request.grant(),
+                                    Some(false) => request.deny(),
+                                    None => window.ask_permission(request),
+                                }
+                            }
+                            ChromiumEvent::CertificateError(error) => {
+                                let decision = settings.borrow().certificate_decision(
+                                    &error.host,
+                                    error.fingerprint.as_deref(),
+                                );
+
+                                match decision {
+                                    Some(true) => error.allow(),
+                                    Some(false) => error.deny(),
+                                    None => window.ask_certificate_error(error),
+                                }
+                            }
+                            ChromiumEvent::ProxyAuthFailed(host) => {
+                                window.show_proxy_auth_failed(&host)
+                            }
+                            ChromiumEvent::DownloadRequest(request) => {
+                                window.choose_download_path(request)
+                            }
+                            ChromiumEvent::Download(download) => window.update_download(download),
+                            ChromiumEvent::ContextMenu(context_menu) => {
+                                if let Some(model) = context_menu::menu_model(
+                                    &context_menu,
+                                    &context_menu_items.borrow(),
+                                    dev_mode,
+                                ) {
+                                    webview.popup_menu(&model, context_menu.x, context_menu.y);
+                                }
+                            }
+                            ChromiumEvent::ImeBounds(x, y, width, height) => {
+                                webview.set_ime_cursor_location(x, y, width, height)
+                            }
                             ChromiumEvent::Ipc(message) => {
                                 if let Ok(event) = ipc::parse_request(&message) {
                                     match event {
@@ -407,6 +845,14 @@ impl ApplicationImpl for Application {
                                             browser.post_message(message);
                                         }
                                         IpcEvent::Ready => {
+                                            // The window may have been restored in fullscreen
+                                            if window.is_fullscreen() {
+                                                let message = ipc::create_response(
+                                                    IpcEvent::Fullscreen(true),
+                                                );
+                                                browser.post_message(message);
+                                            }
+
                                             if let Some(ref uri) = *deeplink.borrow() {
                                                 let message = ipc::create_response(
                                                     IpcEvent::OpenMedia(uri.to_string()),
@@ -417,7 +863,20 @@ impl ApplicationImpl for Application {
                                         IpcEvent::Quit => {
                                             app.quit();
                                         }
+                                        IpcEvent::MiniPlayer(state) => {
+                                            app.change_action_state(
+                                                "mini-player",
+                                                &state.to_variant(),
+                                            );
+                                        }
                                         IpcEvent::Fullscreen(state) => {
+                                            if state && window.is_mini_player() {
+                                                app.change_action_state(
+                                                    "mini-player",
+                                                    &false.to_variant(),
+                                                );
+                                            }
+
                                             window.set_fullscreen(state);
 
                                             let message =
@@ -437,6 +896,12 @@ impl ApplicationImpl for Application {
                                             _ => {}
                                         },
                                         IpcEvent::MetadataUpdate(data) => {
+                                            if let Some(available) = data.next_video {
+                                                mpris_sender
+                                                    .send(UserEvent::NextVideo(available))
+                                                    .ok();
+                                            }
+
                                             mpris_sender
                                                 .send(UserEvent::MetadataUpdate {
                                                     title: data.title,
@@ -444,9 +909,19 @@ impl ApplicationImpl for Application {
                                                     poster: data.poster,
                                                     thumbnail: data.thumbnail,
                                                     logo: data.logo,
+                                                    id: data.id,
+                                                    kind: data.kind,
+                                                    adult: data.genres.map(|genres| {
+                                                        metadata::adult_genres(
+                                                            genres.iter().map(String::as_str),
+                                                        )
+                                                    }),
                                                 })
                                                 .ok();
                                         }
+                                        IpcEvent::ContextMenuItems(items) => {
+                                            *context_menu_items.borrow_mut() = items;
+                                        }
                                         _ => {}
                                     }
                                 }
@@ -475,106 +950,90 @@ impl ApplicationImpl for Application {
             }
         ));
 
-        let browser = self.browser.clone();
-        webview.connect_has_focus_notify(move |_| {
-            if let Some(ref browser) = *browser.borrow() {
-                browser.focus(true);
-            }
-        });
-
-        let browser = self.browser.clone();
-        webview.connect_resized(move |width, height| {
-            if let Some(ref browser) = *browser.borrow() {
-                browser.resize(width, height);
-            }
-        });
-
-        let browser = self.browser.clone();
-        webview.connect_motion(move |pointer_state| {
-            if let Some(ref browser) = *browser.borrow() {
-                browser.forward_motion(&pointer_state);
-            }
-        });
-
-        let browser = self.browser.clone();
-        webview.connect_scroll(move |pointer_state, delta_x, delta_y| {
-            if let Some(ref browser) = *browser.borrow() {
-                browser.forward_scroll(&pointer_state, delta_x, delta_y);
+        tray.connect_show(clone!(
+            #[weak]
+            window,
+            move || {
+                window.set_visible(true);
             }
-        });
+        ));
 
-        let browser = self.browser.clone();
-        webview.connect_click(clone!(
+        tray.connect_hide(clone!(
             #[weak]
-            webview,
-            move |pointer_state, count| {
-                if let Some(ref browser) = *browser.borrow() {
-                    webview.grab_focus();
-                    browser.forward_click(&pointer_state, count);
-                }
+            window,
+            move || {
+                window.set_visible(false);
             }
         ));
 
-        let browser = self.browser.clone();
-        webview.connect_keys(clone!(
+        tray.connect_availability(clone!(
             #[weak]
-            webview,
-            move |keyboard_state| {
-                if let Some(ref browser) = *browser.borrow() {
-                    webview.grab_focus();
-                    browser.forward_key(&keyboard_state);
-                }
+            window,
+            move |available| {
+                window.set_tray_available(available);
             }
         ));
 
-        let browser = self.browser.clone();
-        webview.connect_clipboard(move |text| {
-            if let Some(ref browser) = *browser.borrow() {
-                browser.clipboard(text);
-            }
+        let settings = self.settings.clone();
+        window.connect_close_request(move |window| {
+            let mut settings = settings.borrow_mut();
+            window.store_state(&mut settings.window);
+            settings.save();
+
+            glib::Propagation::Proceed
         });
 
-        let browser = self.browser.clone();
-        webview.connect_file_enter(move |pointer_state, path| {
-            if let Some(ref browser) = *browser.borrow() {
-                browser.forward_file_enter(pointer_state.as_ref(), path);
-            }
+        let settings = self.settings.clone();
+        window.connect_close_action_changed(move |action| {
+            let mut settings = settings.borrow_mut();
+            settings.window.close_action = action;
+            settings.save();
         });
 
-        let browser = self.browser.clone();
-        webview.connect_file_leave(move || {
-            if let Some(ref browser) = *browser.borrow() {
-                browser.forward_file_leave();
-            }
+        let settings = self.settings.clone();
+        window.connect_permission_decision(move |origin, permission, granted| {
+            let mut settings = settings.borrow_mut();
+            settings.remember_permission(&origin, permission, granted);
+            settings.save();
         });
 
-        let browser = self.browser.clone();
-        webview.connect_file_motion(move |pointer_state| {
-            if let Some(ref browser) = *browser.borrow() {
-                browser.forward_file_hover(pointer_state.as_ref());
-            }
+        let settings = self.settings.clone();
+        window.connect_certificate_decision(move |host, fingerprint, allowed| {
+            let mut settings = settings.borrow_mut();
+            settings.remember_certificate(&host, &fingerprint, allowed);
+            settings.save();
         });
 
-        let browser = self.browser.clone();
-        webview.connect_file_drop(move |pointer_state| {
-            if let Some(ref browser) = *browser.borrow() {
-                browser.forward_file_drop(pointer_state.as_ref());
+        tray.connect_mini_player(clone!(
+            #[weak]
+            app,
+            #[weak]
+            window,
+            move || {
+                window.set_visible(true);
+                app.activate_action("mini-player", None);
             }
-        });
+        ));
 
-        tray.connect_show(clone!(
+        tray.connect_settings(clone!(
+            #[weak]
+            app,
             #[weak]
             window,
             move || {
                 window.set_visible(true);
+                app.activate_action("settings", None);
             }
         ));
 
-        tray.connect_hide(clone!(
+        tray.connect_about(clone!(
+            #[weak]
+            app,
             #[weak]
             window,
             move || {
-                window.set_visible(false);
+                window.set_visible(true);
+                app.activate_action("show-about", None);
             }
         ));
 
@@ -611,6 +1070,17 @@ impl ApplicationImpl for Application {
     }
 
     fn shutdown(&self) {
+        if let Some(window) = self
+            .obj()
+            .windows()
+            .into_iter()
+            .find_map(|window| window.downcast::<Window>().ok())
+        {
+            let mut settings = self.settings.borrow_mut();
+            window.store_state(&mut settings.window);
+            settings.save();
+        }
+
         if let Some(browser) = self.browser.take() {
             browser.stop();
         }
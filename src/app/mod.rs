@@ -1,5 +1,8 @@
 mod about;
+mod auxiliary_window;
+mod bindings;
 mod config;
+mod context_menu;
 mod imp;
 mod settings_window;
 mod tray;
@@ -20,6 +23,7 @@ use crate::{
         config::{APP_ID, APP_NAME},
     },
     chromium::Chromium,
+    settings::Settings,
 };
 
 glib::wrapper! {
@@ -48,6 +52,10 @@ impl Application {
         self.imp().set_browser(browser);
     }
 
+    pub fn set_settings(&self, settings: Settings) {
+        self.imp().set_settings(settings);
+    }
+
     pub async fn run(&self) -> ExitCode {
         let args: Vec<String> = vec![];
         self.run_with_args(&args)
@@ -1,7 +1,16 @@
-use adw::subclass::prelude::*;
-use gtk::{CompositeTemplate, glib, prelude::*};
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{CompositeTemplate, glib};
 
-use std::sync::OnceLock;
+use std::{cell::RefCell, sync::OnceLock};
+
+use crate::{
+    settings::{
+        CloseAction, DiscordPrivacy, DiscordSettings, InputSettings, ProxyMode, ProxySettings,
+        WindowSettings,
+    },
+    shared::proxy,
+};
 
 #[derive(Default, CompositeTemplate, glib::Properties)]
 #[template(file = "settings_window.ui")]
@@ -14,7 +23,31 @@ pub struct SettingsWindow {
     #[template_child]
     pub gpu_row: TemplateChild<adw::ActionRow>,
     #[template_child]
+    pub close_action_row: TemplateChild<adw::ComboRow>,
+    #[template_child]
+    pub wheel_multiplier_row: TemplateChild<adw::SpinRow>,
+    #[template_child]
+    pub kinetic_scrolling_row: TemplateChild<adw::SwitchRow>,
+    #[template_child]
+    pub navigation_buttons_row: TemplateChild<adw::SwitchRow>,
+    #[template_child]
+    pub proxy_mode_row: TemplateChild<adw::ComboRow>,
+    #[template_child]
+    pub proxy_url_row: TemplateChild<adw::EntryRow>,
+    #[template_child]
+    pub proxy_pac_url_row: TemplateChild<adw::EntryRow>,
+    #[template_child]
     pub discord_switch: TemplateChild<gtk::Switch>,
+    #[template_child]
+    pub discord_privacy_row: TemplateChild<adw::ComboRow>,
+    #[template_child]
+    pub discord_adult_row: TemplateChild<adw::SwitchRow>,
+    #[template_child]
+    pub discord_buttons_row: TemplateChild<adw::SwitchRow>,
+    #[template_child]
+    pub discord_client_row: TemplateChild<adw::EntryRow>,
+    /// Shown once while changed proxy settings wait for a restart
+    pub restart_toast: RefCell<Option<adw::Toast>>,
 }
 
 #[glib::object_subclass]
@@ -42,9 +75,13 @@ impl ObjectImpl for SettingsWindow {
                 glib::subclass::Signal::builder("fps-toggled")
                     .param_types([bool::static_type()])
                     .build(),
+                glib::subclass::Signal::builder("window-settings-changed").build(),
+                glib::subclass::Signal::builder("input-settings-changed").build(),
+                glib::subclass::Signal::builder("proxy-settings-changed").build(),
                 glib::subclass::Signal::builder("discord-toggled")
                     .param_types([bool::static_type()])
                     .build(),
+                glib::subclass::Signal::builder("discord-settings-changed").build(),
             ]
         })
     }
@@ -79,9 +116,105 @@ impl SettingsWindow {
         self.fps_switch.set_active(active);
     }
 
+    pub fn set_window_settings(&self, settings: &WindowSettings) {
+        self.close_action_row
+            .set_selected(settings.close_action.position());
+    }
+
+    pub fn update_window_settings(&self, settings: &mut WindowSettings) {
+        settings.close_action = CloseAction::from_position(self.close_action_row.selected());
+    }
+
+    pub fn set_input_settings(&self, settings: &InputSettings) {
+        self.wheel_multiplier_row
+            .set_value(settings.wheel_multiplier);
+        self.kinetic_scrolling_row
+            .set_active(settings.kinetic_scrolling);
+        self.navigation_buttons_row
+            .set_active(settings.navigation_buttons);
+    }
+
+    pub fn update_input_settings(&self, settings: &mut InputSettings) {
+        settings.wheel_multiplier = self.wheel_multiplier_row.value();
+        settings.kinetic_scrolling = self.kinetic_scrolling_row.is_active();
+        settings.navigation_buttons = self.navigation_buttons_row.is_active();
+    }
+
+    pub fn set_proxy_settings(&self, settings: &ProxySettings) {
+        self.proxy_mode_row.set_selected(settings.mode.position());
+        self.proxy_url_row.set_text(&settings.url);
+        self.proxy_pac_url_row.set_text(&settings.pac_url);
+        self.update_proxy_rows();
+    }
+
+    pub fn update_proxy_settings(&self, settings: &mut ProxySettings) {
+        settings.mode = ProxyMode::from_position(self.proxy_mode_row.selected());
+        settings.url = self.proxy_url_row.text().trim().to_owned();
+        settings.pac_url = self.proxy_pac_url_row.text().trim().to_owned();
+    }
+
+    /// The browser only reads the proxy settings when it starts
+    fn show_restart_toast(&self) {
+        if self.restart_toast.borrow().is_some() {
+            return;
+        }
+
+        let toast = adw::Toast::builder()
+            .title(gettext("Restart Stremio to use the new proxy settings"))
+            .build();
+
+        let object = self.obj();
+        toast.connect_dismissed(glib::clone!(
+            #[weak]
+            object,
+            move |_| {
+                object.imp().restart_toast.take();
+            }
+        ));
+
+        object.add_toast(toast.clone());
+        self.restart_toast.replace(Some(toast));
+    }
+
+    fn update_proxy_rows(&self) {
+        let mode = ProxyMode::from_position(self.proxy_mode_row.selected());
+        self.proxy_url_row.set_visible(mode == ProxyMode::Manual);
+        self.proxy_pac_url_row.set_visible(mode == ProxyMode::Pac);
+
+        // Invalid urls fall back to the system proxy, make that visible
+        for row in [&*self.proxy_url_row, &*self.proxy_pac_url_row] {
+            let text = row.text();
+
+            if text.is_empty() || proxy::parse_url(&text).is_some() {
+                row.remove_css_class("error");
+            } else {
+                row.add_css_class("error");
+            }
+        }
+    }
+
     pub fn set_discord_active(&self, active: bool) {
         self.discord_switch.set_active(active);
     }
+
+    pub fn set_discord_settings(&self, settings: &DiscordSettings) {
+        self.discord_privacy_row
+            .set_selected(settings.privacy.position());
+        self.discord_adult_row.set_active(settings.hide_adult);
+        self.discord_buttons_row.set_active(settings.buttons);
+        self.discord_client_row.set_text(&settings.client_id);
+    }
+
+    pub fn update_discord_settings(&self, settings: &mut DiscordSettings) {
+        settings.privacy = DiscordPrivacy::from_position(self.discord_privacy_row.selected());
+        settings.hide_adult = self.discord_adult_row.is_active();
+        settings.buttons = self.discord_buttons_row.is_active();
+
+        let client_id = self.discord_client_row.text();
+        if !client_id.trim().is_empty() {
+            settings.client_id = client_id.trim().to_owned();
+        }
+    }
 }
 
 impl WidgetImpl for SettingsWindow {}
@@ -96,9 +229,37 @@ impl SettingsWindow {
         self.obj().emit_by_name::<()>("fps-toggled", &[&active]);
     }
 
+    #[template_callback]
+    fn on_window_settings_changed(&self) {
+        self.obj()
+            .emit_by_name::<()>("window-settings-changed", &[]);
+    }
+
+    #[template_callback]
+    fn on_input_settings_changed(&self) {
+        self.obj().emit_by_name::<()>("input-settings-changed", &[]);
+    }
+
+    #[template_callback]
+    fn on_proxy_settings_changed(&self) {
+        self.update_proxy_rows();
+        self.obj().emit_by_name::<()>("proxy-settings-changed", &[]);
+
+        // Filling in the saved settings before showing the dialog changes nothing
+        if self.obj().is_mapped() {
+            self.show_restart_toast();
+        }
+    }
+
     #[template_callback]
     fn on_discord_toggled(&self, _pspec: &glib::ParamSpec) {
         let active = self.discord_switch.is_active();
         self.obj().emit_by_name::<()>("discord-toggled", &[&active]);
     }
+
+    #[template_callback]
+    fn on_discord_settings_changed(&self) {
+        self.obj()
+            .emit_by_name::<()>("discord-settings-changed", &[]);
+    }
 }
@@ -4,6 +4,8 @@ use adw::subclass::prelude::*;
 use glib::Object;
 use gtk::{glib, prelude::*};
 
+use crate::settings::{DiscordSettings, InputSettings, ProxySettings, WindowSettings};
+
 glib::wrapper! {
     pub struct SettingsWindow(ObjectSubclass<imp::SettingsWindow>)
         @extends gtk::Widget, adw::Dialog, adw::PreferencesDialog,
@@ -19,9 +21,41 @@ impl SettingsWindow {
         self.imp().set_fps_active(active);
     }
 
+    pub fn set_window_settings(&self, settings: &WindowSettings) {
+        self.imp().set_window_settings(settings);
+    }
+
+    pub fn update_window_settings(&self, settings: &mut WindowSettings) {
+        self.imp().update_window_settings(settings);
+    }
+
+    pub fn set_input_settings(&self, settings: &InputSettings) {
+        self.imp().set_input_settings(settings);
+    }
+
+    pub fn update_input_settings(&self, settings: &mut InputSettings) {
+        self.imp().update_input_settings(settings);
+    }
+
+    pub fn set_proxy_settings(&self, settings: &ProxySettings) {
+        self.imp().set_proxy_settings(settings);
+    }
+
+    pub fn update_proxy_settings(&self, settings: &mut ProxySettings) {
+        self.imp().update_proxy_settings(settings);
+    }
+
     pub fn set_discord_active(&self, active: bool) {
         self.imp().set_discord_active(active);
     }
+
+    pub fn set_discord_settings(&self, settings: &DiscordSettings) {
+        self.imp().set_discord_settings(settings);
+    }
+
+    pub fn update_discord_settings(&self, settings: &mut DiscordSettings) {
+        self.imp().update_discord_settings(settings);
+    }
 }
 
 impl Default for SettingsWindow {
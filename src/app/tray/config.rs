@@ -2,3 +2,13 @@ pub const ICON_FILE: &[u8] = include_bytes!(concat!(
     env!("CARGO_MANIFEST_DIR"),
     "/data/icons/symbolic.png"
 ));
+
+/// Background of the playback state badge drawn over the icon
+pub const BADGE_COLOR: [u8; 4] = [123, 91, 245, 255];
+/// Foreground of the playback state badge
+pub const GLYPH_COLOR: [u8; 4] = [255, 255, 255, 255];
+
+/// Seconds skipped by the rewind and forward items
+pub const SEEK_OFFSET: i64 = 10;
+/// Percentage added or removed by the volume items
+pub const VOLUME_STEP: f64 = 10.0;
@@ -0,0 +1,88 @@
+use std::sync::LazyLock;
+
+use image::{Rgba, RgbaImage};
+
+use crate::app::tray::config::{BADGE_COLOR, GLYPH_COLOR, ICON_FILE};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconOverlay {
+    Playing,
+    Paused,
+    Buffering,
+}
+
+pub fn pixmap(overlay: Option<IconOverlay>) -> ksni::Icon {
+    static DEFAULT: LazyLock<ksni::Icon> = LazyLock::new(|| load_icon(None));
+    static PLAYING: LazyLock<ksni::Icon> = LazyLock::new(|| load_icon(Some(IconOverlay::Playing)));
+    static PAUSED: LazyLock<ksni::Icon> = LazyLock::new(|| load_icon(Some(IconOverlay::Paused)));
+    static BUFFERING: LazyLock<ksni::Icon> =
+        LazyLock::new(|| load_icon(Some(IconOverlay::Buffering)));
+
+    match overlay {
+        None => DEFAULT.clone(),
+        Some(IconOverlay::Playing) => PLAYING.clone(),
+        Some(IconOverlay::Paused) => PAUSED.clone(),
+        Some(IconOverlay::Buffering) => BUFFERING.clone(),
+    }
+}
+
+fn load_icon(overlay: Option<IconOverlay>) -> ksni::Icon {
+    let mut image = image::load_from_memory(ICON_FILE)
+        .expect("Failed to open icon path")
+        .into_rgba8();
+
+    if let Some(overlay) = overlay {
+        draw_badge(&mut image, overlay);
+    }
+
+    let (width, height) = image.dimensions();
+    let mut data = image.into_raw();
+
+    for pixel in data.chunks_exact_mut(4) {
+        pixel.rotate_right(1) // rgba to argb
+    }
+
+    ksni::Icon {
+        width: width as i32,
+        height: height as i32,
+        data,
+    }
+}
+
+/// Draws a round badge in the bottom right corner with the overlay glyph inside
+fn draw_badge(image: &mut RgbaImage, overlay: IconOverlay) {
+    let (width, height) = image.dimensions();
+    let radius = (width.min(height) as f32 / 4.0).max(4.0);
+    let center_x = width as f32 - radius;
+    let center_y = height as f32 - radius;
+
+    for y in 0..height {
+        for x in 0..width {
+            // Normalized coordinates relative to the badge center
+            let dx = (x as f32 + 0.5 - center_x) / radius;
+            let dy = (y as f32 + 0.5 - center_y) / radius;
+
+            if dx * dx + dy * dy > 1.0 {
+                continue;
+            }
+
+            let color = if is_glyph(overlay, dx, dy) {
+                GLYPH_COLOR
+            } else {
+                BADGE_COLOR
+            };
+
+            image.put_pixel(x, y, Rgba(color));
+        }
+    }
+}
+
+fn is_glyph(overlay: IconOverlay, x: f32, y: f32) -> bool {
+    match overlay {
+        IconOverlay::Playing => (-0.3..=0.5).contains(&x) && y.abs() <= 0.5 * (0.5 - x) / 0.8,
+        IconOverlay::Paused => y.abs() <= 0.45 && (0.1..=0.4).contains(&x.abs()),
+        IconOverlay::Buffering => [-0.45, 0.0, 0.45]
+            .iter()
+            .any(|center| (x - center).powi(2) + y * y <= 0.16 * 0.16),
+    }
+}
@@ -1,19 +1,29 @@
 use std::sync::{
-    Arc, LazyLock, OnceLock,
+    Arc, OnceLock,
     mpsc::{Sender, channel},
 };
 
 use gettextrs::gettext;
 use gtk::{
-    glib::{self, object::ObjectExt, subclass::Signal},
+    glib::{self, object::ObjectExt, subclass::Signal, types::StaticType},
     subclass::prelude::*,
 };
-use ksni::{Handle, MenuItem, TrayMethods, menu::StandardItem};
+use ksni::{
+    Handle, MenuItem, OfflineReason, Orientation, ToolTip, TrayMethods,
+    menu::{CheckmarkItem, StandardItem, SubMenu},
+};
 use tokio::sync::Mutex;
+use tracing::{error, warn};
 
-use crate::app::{
-    config::{APP_ID, APP_NAME},
-    tray::config::ICON_FILE,
+use crate::{
+    app::{
+        config::{APP_ID, APP_NAME},
+        tray::{
+            config::{SEEK_OFFSET, VOLUME_STEP},
+            icon::{self, IconOverlay},
+        },
+    },
+    shared::types::{MprisCommand, PlaybackStatus},
 };
 
 #[derive(Default)]
@@ -23,11 +33,52 @@ pub struct Tray {
 
 impl Tray {
     pub fn update(&self, state: bool) {
+        self.update_icon(move |tray| tray.window_visible = state);
+    }
+
+    pub fn update_playback(&self, status: PlaybackStatus) {
+        self.update_icon(move |tray| tray.playback.status = status);
+    }
+
+    pub fn update_now_playing(&self, title: Option<String>, artist: Option<String>) {
+        self.update_icon(move |tray| {
+            if title.is_some() {
+                tray.playback.title = title;
+                tray.playback.artist = artist;
+            }
+        });
+    }
+
+    pub fn update_buffering(&self, buffering: bool) {
+        self.update_icon(move |tray| tray.playback.buffering = buffering);
+    }
+
+    pub fn update_volume(&self, volume: f64) {
+        self.update_icon(move |tray| tray.playback.volume = volume);
+    }
+
+    pub fn update_mini_player(&self, active: bool) {
+        self.update_icon(move |tray| tray.mini_player = active);
+    }
+
+    pub fn update_mute(&self, muted: bool) {
+        self.update_icon(move |tray| tray.playback.muted = muted);
+    }
+
+    pub fn update_next_video(&self, available: bool) {
+        self.update_icon(move |tray| tray.playback.has_next = available);
+    }
+
+    pub fn clear_playback(&self) {
+        self.update_icon(|tray| tray.playback = Default::default());
+    }
+
+    fn update_icon<F: FnOnce(&mut TrayIcon) + Send + 'static>(&self, f: F) {
         let local_handle = self.handle.clone();
         tokio::spawn(async move {
             let handle_guard = local_handle.lock().await;
             if let Some(handle) = handle_guard.as_ref() {
-                handle.update(|tray| tray.window_visible = state).await;
+                handle.update(f).await;
             }
         });
     }
@@ -48,6 +99,15 @@ impl ObjectImpl for Tray {
                 Signal::builder("show").build(),
                 Signal::builder("hide").build(),
                 Signal::builder("quit").build(),
+                Signal::builder("mini-player").build(),
+                Signal::builder("settings").build(),
+                Signal::builder("about").build(),
+                Signal::builder("availability")
+                    .param_types([bool::static_type()])
+                    .build(),
+                Signal::builder("command")
+                    .param_types([MprisCommand::static_type()])
+                    .build(),
             ]
         })
     }
@@ -58,18 +118,25 @@ impl ObjectImpl for Tray {
         let (sender, receiver) = channel::<TrayEvent>();
 
         let tray_icon = TrayIcon {
-            sender,
+            sender: sender.clone(),
             window_visible: true,
+            mini_player: false,
+            playback: Default::default(),
         };
 
         let local_handle = self.handle.clone();
         tokio::spawn(async move {
             let mut handle_guard = local_handle.lock().await;
-            let handle = tray_icon
-                .spawn_without_dbus_name()
-                .await
-                .expect("Failed to create tray icon");
-            *handle_guard = Some(handle);
+            match tray_icon.spawn_without_dbus_name().await {
+                Ok(handle) => {
+                    *handle_guard = Some(handle);
+                    sender.send(TrayEvent::Availability(true)).ok();
+                }
+                Err(e) => {
+                    error!("Failed to create tray icon: {e}");
+                    sender.send(TrayEvent::Availability(false)).ok();
+                }
+            }
         });
 
         let object_weak = self.obj().downgrade();
@@ -80,6 +147,15 @@ impl ObjectImpl for Tray {
                         TrayEvent::Show => object.emit_by_name::<()>("show", &[]),
                         TrayEvent::Hide => object.emit_by_name::<()>("hide", &[]),
                         TrayEvent::Quit => object.emit_by_name::<()>("quit", &[]),
+                        TrayEvent::MiniPlayer => object.emit_by_name::<()>("mini-player", &[]),
+                        TrayEvent::Settings => object.emit_by_name::<()>("settings", &[]),
+                        TrayEvent::About => object.emit_by_name::<()>("about", &[]),
+                        TrayEvent::Availability(available) => {
+                            object.emit_by_name::<()>("availability", &[&available])
+                        }
+                        TrayEvent::Command(command) => {
+                            object.emit_by_name::<()>("command", &[&command])
+                        }
                     }
                 }
             });
@@ -94,11 +170,83 @@ pub enum TrayEvent {
     Show,
     Hide,
     Quit,
+    MiniPlayer,
+    Settings,
+    About,
+    Availability(bool),
+    Command(MprisCommand),
+}
+
+#[derive(Debug, Clone)]
+pub struct TrayPlayback {
+    status: PlaybackStatus,
+    title: Option<String>,
+    artist: Option<String>,
+    buffering: bool,
+    volume: f64,
+    muted: bool,
+    has_next: bool,
+}
+
+impl Default for TrayPlayback {
+    fn default() -> Self {
+        Self {
+            status: PlaybackStatus::Stopped,
+            title: None,
+            artist: None,
+            buffering: false,
+            volume: 100.0,
+            muted: false,
+            has_next: false,
+        }
+    }
+}
+
+impl TrayPlayback {
+    fn active(&self) -> bool {
+        self.status != PlaybackStatus::Stopped
+    }
+
+    fn overlay(&self) -> Option<IconOverlay> {
+        match self.status {
+            PlaybackStatus::Stopped => None,
+            _ if self.buffering => Some(IconOverlay::Buffering),
+            PlaybackStatus::Playing => Some(IconOverlay::Playing),
+            PlaybackStatus::Paused => Some(IconOverlay::Paused),
+        }
+    }
+
+    fn now_playing(&self) -> Option<String> {
+        let title = self.title.as_deref()?;
+
+        match self.artist.as_deref() {
+            Some(artist) if !artist.is_empty() && artist != title => {
+                Some(format!("{artist} - {title}"))
+            }
+            _ => Some(title.to_owned()),
+        }
+    }
 }
 
 pub struct TrayIcon {
     sender: Sender<TrayEvent>,
     window_visible: bool,
+    mini_player: bool,
+    playback: TrayPlayback,
+}
+
+impl TrayIcon {
+    fn command_item(&self, label: String, command: MprisCommand) -> MenuItem<Self> {
+        StandardItem {
+            label,
+            enabled: self.playback.active(),
+            activate: Box::new(move |tray: &mut Self| {
+                tray.sender.send(TrayEvent::Command(command)).ok();
+            }),
+            ..Default::default()
+        }
+        .into()
+    }
 }
 
 impl ksni::Tray for TrayIcon {
@@ -111,25 +259,143 @@ impl ksni::Tray for TrayIcon {
     }
 
     fn icon_pixmap(&self) -> Vec<ksni::Icon> {
-        static ICON: LazyLock<ksni::Icon> = LazyLock::new(|| {
-            let (data, width, height) = load_image(ICON_FILE);
+        vec![icon::pixmap(self.playback.overlay())]
+    }
 
-            ksni::Icon {
-                width: width as i32,
-                height: height as i32,
-                data,
-            }
-        });
+    fn activate(&mut self, _x: i32, _y: i32) {
+        let event = if self.window_visible {
+            TrayEvent::Hide
+        } else {
+            TrayEvent::Show
+        };
+
+        self.sender.send(event).ok();
+    }
+
+    fn scroll(&mut self, delta: i32, orientation: Orientation) {
+        if orientation != Orientation::Vertical || delta == 0 || !self.playback.active() {
+            return;
+        }
+
+        let offset = VOLUME_STEP.copysign(delta as f64);
+        self.sender
+            .send(TrayEvent::Command(MprisCommand::AdjustVolume(offset)))
+            .ok();
+    }
 
-        vec![ICON.clone()]
+    fn watcher_online(&self) {
+        self.sender.send(TrayEvent::Availability(true)).ok();
+    }
+
+    fn watcher_offline(&self, reason: OfflineReason) -> bool {
+        warn!("Tray host is offline: {reason:?}");
+        self.sender.send(TrayEvent::Availability(false)).ok();
+
+        // Keep the service alive so the icon comes back with the host
+        true
+    }
+
+    fn tool_tip(&self) -> ToolTip {
+        let description = match self.playback.now_playing() {
+            Some(now_playing) if self.playback.active() => now_playing,
+            _ => String::new(),
+        };
+
+        ToolTip {
+            title: APP_NAME.into(),
+            description,
+            ..Default::default()
+        }
     }
 
     fn menu(&self) -> Vec<MenuItem<Self>> {
         let sender_show = self.sender.clone();
         let sender_hide = self.sender.clone();
         let sender_quit = self.sender.clone();
+        let sender_settings = self.sender.clone();
+        let sender_about = self.sender.clone();
+
+        let now_playing = self
+            .playback
+            .now_playing()
+            .filter(|_| self.playback.active());
+
+        let play_pause_label = match self.playback.status {
+            PlaybackStatus::Playing => gettext("Pause"),
+            _ => gettext("Play"),
+        };
+
+        let volume = self.playback.volume.round() as i32;
 
         vec![
+            StandardItem {
+                label: gettext("Now playing: {}")
+                    .replace("{}", now_playing.as_deref().unwrap_or_default()),
+                enabled: false,
+                visible: now_playing.is_some(),
+                ..Default::default()
+            }
+            .into(),
+            MenuItem::Separator,
+            self.command_item(play_pause_label, MprisCommand::PlayPause),
+            self.command_item(gettext("Stop"), MprisCommand::Stop),
+            self.command_item(
+                gettext("Rewind {}s").replace("{}", &SEEK_OFFSET.to_string()),
+                MprisCommand::Seek(-SEEK_OFFSET * 1_000_000),
+            ),
+            self.command_item(
+                gettext("Forward {}s").replace("{}", &SEEK_OFFSET.to_string()),
+                MprisCommand::Seek(SEEK_OFFSET * 1_000_000),
+            ),
+            StandardItem {
+                label: gettext("Next episode"),
+                enabled: self.playback.active() && self.playback.has_next,
+                activate: Box::new(|tray: &mut Self| {
+                    tray.sender
+                        .send(TrayEvent::Command(MprisCommand::Next))
+                        .ok();
+                }),
+                ..Default::default()
+            }
+            .into(),
+            SubMenu {
+                label: gettext("Volume ({}%)").replace("{}", &volume.to_string()),
+                enabled: self.playback.active(),
+                submenu: vec![
+                    self.command_item(
+                        gettext("Volume up"),
+                        MprisCommand::AdjustVolume(VOLUME_STEP),
+                    ),
+                    self.command_item(
+                        gettext("Volume down"),
+                        MprisCommand::AdjustVolume(-VOLUME_STEP),
+                    ),
+                ],
+                ..Default::default()
+            }
+            .into(),
+            CheckmarkItem {
+                label: gettext("Mute"),
+                enabled: self.playback.active(),
+                checked: self.playback.muted,
+                activate: Box::new(|tray: &mut Self| {
+                    tray.sender
+                        .send(TrayEvent::Command(MprisCommand::ToggleMute))
+                        .ok();
+                }),
+                ..Default::default()
+            }
+            .into(),
+            MenuItem::Separator,
+            CheckmarkItem {
+                label: gettext("Mini Player"),
+                checked: self.mini_player,
+                activate: Box::new(|tray: &mut Self| {
+                    tray.sender.send(TrayEvent::MiniPlayer).ok();
+                }),
+                ..Default::default()
+            }
+            .into(),
             StandardItem {
                 label: gettext("Show"),
                 visible: !self.window_visible,
@@ -148,6 +414,24 @@ impl ksni::Tray for TrayIcon {
                 ..Default::default()
             }
             .into(),
+            MenuItem::Separator,
+            StandardItem {
+                label: gettext("_Settings"),
+                activate: Box::new(move |_| {
+                    sender_settings.send(TrayEvent::Settings).ok();
+                }),
+                ..Default::default()
+            }
+            .into(),
+            StandardItem {
+                label: gettext("_About Stremio"),
+                activate: Box::new(move |_| {
+                    sender_about.send(TrayEvent::About).ok();
+                }),
+                ..Default::default()
+            }
+            .into(),
+            MenuItem::Separator,
             StandardItem {
                 label: gettext("Quit"),
                 activate: Box::new(move |_| {
@@ -159,18 +443,3 @@ impl ksni::Tray for TrayIcon {
         ]
     }
 }
-
-fn load_image(buffer: &[u8]) -> (Vec<u8>, u32, u32) {
-    let image = image::load_from_memory(buffer)
-        .expect("Failed to open icon path")
-        .into_rgba8();
-
-    let (width, height) = image.dimensions();
-    let mut data = image.into_raw();
-
-    for pixel in data.chunks_exact_mut(4) {
-        pixel.rotate_right(1) // rgba to argb
-    }
-
-    (data, width, height)
-}
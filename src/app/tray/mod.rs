@@ -1,9 +1,12 @@
 mod config;
+mod icon;
 mod imp;
 
 use adw::subclass::prelude::ObjectSubclassIsExt;
 use gtk::glib::{self, closure_local, object::ObjectExt};
 
+use crate::shared::types::{MprisCommand, PlaybackStatus};
+
 glib::wrapper! {
     pub struct Tray(ObjectSubclass<imp::Tray>);
 }
@@ -19,6 +22,38 @@ impl Tray {
         self.imp().update(state);
     }
 
+    pub fn update_playback(&self, status: PlaybackStatus) {
+        self.imp().update_playback(status);
+    }
+
+    pub fn update_now_playing(&self, title: Option<String>, artist: Option<String>) {
+        self.imp().update_now_playing(title, artist);
+    }
+
+    pub fn update_buffering(&self, buffering: bool) {
+        self.imp().update_buffering(buffering);
+    }
+
+    pub fn update_volume(&self, volume: f64) {
+        self.imp().update_volume(volume);
+    }
+
+    pub fn update_mini_player(&self, active: bool) {
+        self.imp().update_mini_player(active);
+    }
+
+    pub fn update_mute(&self, muted: bool) {
+        self.imp().update_mute(muted);
+    }
+
+    pub fn update_next_video(&self, available: bool) {
+        self.imp().update_next_video(available);
+    }
+
+    pub fn clear_playback(&self) {
+        self.imp().clear_playback();
+    }
+
     pub fn connect_show<T: Fn() + 'static>(&self, callback: T) {
         self.connect_closure(
             "show",
@@ -48,4 +83,54 @@ impl Tray {
             }),
         );
     }
+
+    pub fn connect_mini_player<T: Fn() + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "mini-player",
+            false,
+            closure_local!(move |_: Tray| {
+                callback();
+            }),
+        );
+    }
+
+    pub fn connect_settings<T: Fn() + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "settings",
+            false,
+            closure_local!(move |_: Tray| {
+                callback();
+            }),
+        );
+    }
+
+    pub fn connect_about<T: Fn() + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "about",
+            false,
+            closure_local!(move |_: Tray| {
+                callback();
+            }),
+        );
+    }
+
+    pub fn connect_availability<T: Fn(bool) + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "availability",
+            false,
+            closure_local!(move |_: Tray, available: bool| {
+                callback(available);
+            }),
+        );
+    }
+
+    pub fn connect_command<T: Fn(MprisCommand) + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "command",
+            false,
+            closure_local!(move |_: Tray, command: MprisCommand| {
+                callback(command);
+            }),
+        );
+    }
 }
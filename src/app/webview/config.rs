@@ -0,0 +1,9 @@
+/// Rate at which kinetic scrolling slows down, per second
+pub const KINETIC_FRICTION: f64 = 4.0;
+/// Velocity, in pixels per second, under which kinetic scrolling stops
+pub const KINETIC_MIN_VELOCITY: f64 = 30.0;
+
+/// Horizontal velocity, in pixels per second, a swipe needs to navigate
+pub const SWIPE_MIN_VELOCITY: f64 = 1000.0;
+/// Number of touchpad fingers used for swipe navigation
+pub const SWIPE_FINGERS: u32 = 3;
@@ -1,4 +1,9 @@
-use std::{cell::Cell, rc::Rc, sync::OnceLock};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+    sync::OnceLock,
+};
 
 use adw::subclass::prelude::*;
 use crossbeam_queue::SegQueue;
@@ -7,9 +12,9 @@ use epoxy::{
     *,
 };
 use gtk::{
-    DropTarget,
-    gdk::{DragAction, FileList, GLContext},
-    glib::{self, ControlFlow, Propagation, Properties},
+    DropTarget, EventControllerFocus,
+    gdk::{DragAction, EventSequence, FileList, GLContext},
+    glib::{self, ControlFlow, Propagation, Properties, clone},
     prelude::*,
 };
 
@@ -29,6 +34,11 @@ pub const VERTEX_SRC: &str = include_str!("shader.vert");
 pub struct WebView {
     #[property(get, set)]
     scale_factor: Cell<i32>,
+    /// Pixels scrolled per mouse wheel notch
+    #[property(get, set)]
+    wheel_multiplier: Cell<f64>,
+    #[property(get, set)]
+    kinetic_scrolling: Cell<bool>,
     program: Cell<GLuint>,
     vao: Cell<GLuint>,
     vbo: Cell<GLuint>,
@@ -41,6 +51,15 @@ pub struct WebView {
     last_height: Cell<i32>,
     pub pointer_state: Rc<PointerState>,
     pub keyboard_state: Rc<KeyboardState>,
+    pub im_context: gtk::IMMulticontext,
+    // Text committed by the input method while it filters a key press
+    pub im_key_press: Cell<bool>,
+    pub im_commit: RefCell<Option<String>>,
+    /// Set while the page shows a composition, until it is committed or cancelled
+    pub im_composing: Cell<bool>,
+    pub selection: RefCell<String>,
+    pub touch_sequences: RefCell<HashMap<EventSequence, i32>>,
+    pub kinetic_scroll: RefCell<Option<gtk::TickCallbackId>>,
     pub frames: Box<SegQueue<Frame>>,
     // FPS Tracking
     pub fps_last_time: Cell<Option<std::time::Instant>>,
@@ -63,6 +82,12 @@ impl ObjectImpl for WebView {
                 glib::subclass::Signal::builder("fps-update")
                     .param_types([u32::static_type()])
                     .build(),
+                glib::subclass::Signal::builder("ime-preedit")
+                    .param_types([String::static_type(), i32::static_type()])
+                    .build(),
+                glib::subclass::Signal::builder("ime-commit")
+                    .param_types([String::static_type()])
+                    .build(),
             ]
         })
     }
@@ -70,8 +95,76 @@ impl ObjectImpl for WebView {
     fn constructed(&self) {
         self.parent_constructed();
 
+        let object = self.obj();
+
         let drop_target = DropTarget::new(FileList::static_type(), DragAction::COPY);
-        self.obj().add_controller(drop_target);
+        object.add_controller(drop_target);
+
+        self.im_context.set_client_widget(Some(&*object));
+        self.im_context.set_use_preedit(true);
+
+        self.im_context.connect_preedit_changed(clone!(
+            #[weak]
+            object,
+            move |im_context| {
+                let (text, _, cursor) = im_context.preedit_string();
+
+                if !text.is_empty() {
+                    object.imp().im_composing.set(true);
+                    object.emit_by_name::<()>("ime-preedit", &[&text.to_string(), &cursor]);
+                    return;
+                }
+
+                // Input methods clear the preedit right before committing it, only cancel
+                // the composition when no commit followed
+                glib::idle_add_local_once(clone!(
+                    #[weak]
+                    object,
+                    move || {
+                        if object.imp().im_composing.replace(false) {
+                            object.emit_by_name::<()>("ime-preedit", &[&String::new(), &0]);
+                        }
+                    }
+                ));
+            }
+        ));
+
+        self.im_context.connect_commit(clone!(
+            #[weak]
+            object,
+            move |_, text| {
+                let imp = object.imp();
+
+                // Keep text committed while filtering a key press for its char events,
+                // unless it ends a composition the page has to replace
+                if imp.im_key_press.get() && !imp.im_composing.get() {
+                    imp.im_commit
+                        .borrow_mut()
+                        .get_or_insert_default()
+                        .push_str(text);
+                } else {
+                    imp.im_composing.set(false);
+                    object.emit_by_name::<()>("ime-commit", &[&text.to_owned()]);
+                }
+            }
+        ));
+
+        let focus_controller = EventControllerFocus::new();
+        focus_controller.connect_enter(clone!(
+            #[weak]
+            object,
+            move |_| {
+                object.imp().im_context.focus_in();
+            }
+        ));
+        focus_controller.connect_leave(clone!(
+            #[weak]
+            object,
+            move |_| {
+                object.imp().im_context.focus_out();
+            }
+        ));
+        object.add_controller(focus_controller);
     }
 }
 
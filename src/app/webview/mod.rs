@@ -1,26 +1,36 @@
+mod config;
 mod gl;
 mod imp;
 
-use std::{path::PathBuf, rc::Rc, sync::OnceLock};
+use std::{cell::Cell, path::PathBuf, rc::Rc, sync::OnceLock};
 pub static GPU_RENDERER: OnceLock<String> = OnceLock::new();
 
 use adw::subclass::prelude::ObjectSubclassIsExt;
 use gtk::{
-    DropTarget, EventControllerKey, EventControllerMotion, EventControllerScroll,
-    EventControllerScrollFlags, GestureClick,
-    gdk::{Display, DragAction, FileList, Key, ModifierType, ScrollUnit, prelude::DisplayExt},
-    gio::{Cancellable, prelude::FileExt},
+    Align, DropTarget, EventControllerKey, EventControllerLegacy, EventControllerMotion,
+    EventControllerScroll, EventControllerScrollFlags, GestureClick, GestureSwipe, GestureZoom,
+    PopoverMenu, PropagationPhase,
+    gdk::{
+        self, DragAction, EventType, FileList, Key, ModifierType, ScrollUnit,
+        prelude::{DeviceExt, DisplayExt},
+    },
+    gio::{self, Cancellable, prelude::FileExt},
     glib::{
-        self, Object, Priority, Propagation,
+        self, ControlFlow, Object, Priority, Propagation, clone,
         object::{Cast, IsA},
         types::StaticType,
     },
+    graphene,
     prelude::*,
 };
 
+use crate::app::webview::config::{
+    KINETIC_FRICTION, KINETIC_MIN_VELOCITY, SWIPE_FINGERS, SWIPE_MIN_VELOCITY,
+};
 use crate::shared::{
     Frame,
-    states::{KeyboardState, PointerState},
+    states::{KeyboardState, PointerState, TouchPhase, TouchPoint},
+    types::{BrowserCursor, ClipboardAction},
 };
 
 glib::wrapper! {
@@ -69,9 +79,10 @@ impl WebView {
 
         let motion_callback = callback.clone();
         let pointer_state = self.imp().pointer_state.clone();
-        event_controller_motion.connect_motion(move |_, x, y| {
+        event_controller_motion.connect_motion(move |controller, x, y| {
             pointer_state.set_over(true);
             pointer_state.set_position(x, y);
+            pointer_state.set_modifiers(controller.current_event_state());
 
             motion_callback(pointer_state.clone());
         });
@@ -87,27 +98,121 @@ impl WebView {
         self.add_controller(event_controller_motion);
     }
 
-    pub fn connect_scroll<T: Fn(Rc<PointerState>, f64, f64) + 'static>(&self, callback: T) {
+    pub fn connect_scroll<T: Fn(Rc<PointerState>, i32, i32) + 'static>(&self, callback: T) {
         let pointer_state = self.imp().pointer_state.clone();
+        let remainder = Cell::new((0.0, 0.0));
+
+        // CEF only takes whole pixels, carry the fractions over to the next event
+        let scroll = Rc::new(move |delta_x: f64, delta_y: f64| {
+            let (remainder_x, remainder_y) = remainder.get();
+            // GTK scrolls down on positive deltas, CEF scrolls up
+            let (x, y) = (remainder_x - delta_x, remainder_y - delta_y);
+            let (whole_x, whole_y) = (x.trunc(), y.trunc());
+            remainder.set((x - whole_x, y - whole_y));
+
+            if whole_x != 0.0 || whole_y != 0.0 {
+                callback(pointer_state.clone(), whole_x as i32, whole_y as i32);
+            }
+        });
 
         let flags = EventControllerScrollFlags::BOTH_AXES | EventControllerScrollFlags::KINETIC;
-        let event_controller_motion = EventControllerScroll::new(flags);
+        let event_controller_scroll = EventControllerScroll::new(flags);
 
-        event_controller_motion.connect_scroll(move |controller, delta_x, delta_y| {
-            match controller.unit() {
-                ScrollUnit::Wheel => {
-                    callback(pointer_state.clone(), delta_x * -200.0, delta_y * -200.0);
+        event_controller_scroll.connect_scroll_begin(clone!(
+            #[weak(rename_to = webview)]
+            self,
+            move |_| {
+                webview.stop_kinetic_scroll();
+            }
+        ));
+
+        event_controller_scroll.connect_scroll(clone!(
+            #[weak(rename_to = webview)]
+            self,
+            #[strong]
+            scroll,
+            #[upgrade_or]
+            Propagation::Proceed,
+            move |controller, delta_x, delta_y| {
+                let pointer_state = &webview.imp().pointer_state;
+                pointer_state.set_modifiers(controller.current_event_state());
+
+                match controller.unit() {
+                    // Notches, fractions of one on high resolution wheels
+                    ScrollUnit::Wheel => {
+                        let multiplier = webview.wheel_multiplier();
+                        scroll(delta_x * multiplier, delta_y * multiplier);
+                    }
+                    // Logical pixels, scaled like the view
+                    ScrollUnit::Surface => {
+                        let scale = webview.surface_scale();
+                        scroll(delta_x * scale, delta_y * scale);
+                    }
+                    _ => return Propagation::Proceed,
                 }
-                ScrollUnit::Surface => {
-                    callback(pointer_state.clone(), delta_x * 2.0, delta_y * 2.0);
+
+                Propagation::Stop
+            }
+        ));
+
+        event_controller_scroll.connect_decelerate(clone!(
+            #[weak(rename_to = webview)]
+            self,
+            move |controller, velocity_x, velocity_y| {
+                if webview.kinetic_scrolling() && controller.unit() == ScrollUnit::Surface {
+                    let scale = webview.surface_scale();
+                    webview.start_kinetic_scroll(
+                        velocity_x * scale,
+                        velocity_y * scale,
+                        scroll.clone(),
+                    );
                 }
-                _ => {}
             }
+        ));
 
-            Propagation::Proceed
+        self.add_controller(event_controller_scroll);
+    }
+
+    /// Scale of surface scroll deltas, unset until the monitor is known
+    fn surface_scale(&self) -> f64 {
+        self.scale_factor().max(1) as f64
+    }
+
+    fn start_kinetic_scroll(&self, velocity_x: f64, velocity_y: f64, scroll: Rc<dyn Fn(f64, f64)>) {
+        self.stop_kinetic_scroll();
+
+        let velocity = Cell::new((velocity_x, velocity_y));
+        let last_frame_time = Cell::new(None);
+
+        let tick_callback = self.add_tick_callback(move |webview, frame_clock| {
+            // Frame times are in microseconds
+            let frame_time = frame_clock.frame_time();
+            let elapsed = last_frame_time
+                .replace(Some(frame_time))
+                .map(|last| (frame_time - last) as f64 / 1_000_000.0)
+                .unwrap_or_default();
+
+            let (velocity_x, velocity_y) = velocity.get();
+            scroll(velocity_x * elapsed, velocity_y * elapsed);
+
+            let decay = (-KINETIC_FRICTION * elapsed).exp();
+            velocity.set((velocity_x * decay, velocity_y * decay));
+
+            if velocity_x.hypot(velocity_y) < KINETIC_MIN_VELOCITY {
+                webview.imp().kinetic_scroll.take();
+                return ControlFlow::Break;
+            }
+
+            ControlFlow::Continue
         });
 
-        self.add_controller(event_controller_motion);
+        self.imp().kinetic_scroll.replace(Some(tick_callback));
+    }
+
+    fn stop_kinetic_scroll(&self) {
+        if let Some(tick_callback) = self.imp().kinetic_scroll.take() {
+            tick_callback.remove();
+        }
     }
 
     pub fn connect_click<T: Fn(Rc<PointerState>, i32) + 'static>(&self, callback: T) {
@@ -118,9 +223,15 @@ impl WebView {
         let pressed_pointer_state = self.imp().pointer_state.clone();
 
         gesture_click.connect_pressed(move |gesture, count, x, y| {
+            // Touches were already sent as touch events
+            if is_touch(gesture) {
+                return;
+            }
+
             pressed_pointer_state.set_position(x, y);
             pressed_pointer_state.set_pressed(true);
             pressed_pointer_state.set_button(gesture.current_button());
+            pressed_pointer_state.set_modifiers(gesture.current_event_state());
 
             pressed_callback(pressed_pointer_state.clone(), count);
         });
@@ -129,9 +240,14 @@ impl WebView {
         let released_pointer_state = self.imp().pointer_state.clone();
 
         gesture_click.connect_released(move |gesture, count, x, y| {
+            if is_touch(gesture) {
+                return;
+            }
+
             released_pointer_state.set_position(x, y);
             released_pointer_state.set_pressed(false);
             released_pointer_state.set_button(gesture.current_button());
+            released_pointer_state.set_modifiers(gesture.current_event_state());
 
             released_callback(released_pointer_state.clone(), count);
         });
@@ -139,18 +255,111 @@ impl WebView {
         self.add_controller(gesture_click);
     }
 
+    pub fn connect_touch<T: Fn(TouchPoint) + 'static>(&self, callback: T) {
+        let event_controller_legacy = EventControllerLegacy::new();
+        // Touches go straight to the page, before the gestures also watching them
+        event_controller_legacy.set_propagation_phase(PropagationPhase::Capture);
+
+        event_controller_legacy.connect_event(clone!(
+            #[weak(rename_to = webview)]
+            self,
+            #[upgrade_or]
+            Propagation::Proceed,
+            move |_, event| {
+                let phase = match event.event_type() {
+                    EventType::TouchBegin => TouchPhase::Pressed,
+                    EventType::TouchUpdate => TouchPhase::Moved,
+                    EventType::TouchEnd => TouchPhase::Released,
+                    EventType::TouchCancel => TouchPhase::Cancelled,
+                    _ => return Propagation::Proceed,
+                };
+
+                let Some((x, y)) = webview.event_position(event) else {
+                    return Propagation::Proceed;
+                };
+
+                let sequence = event.event_sequence();
+                let mut sequences = webview.imp().touch_sequences.borrow_mut();
+
+                // CEF wants small ids, reuse the lowest one that is free
+                let free_id = (0..)
+                    .find(|id| !sequences.values().any(|used| used == id))
+                    .unwrap_or_default();
+                let id = *sequences.entry(sequence.clone()).or_insert(free_id);
+
+                if matches!(phase, TouchPhase::Released | TouchPhase::Cancelled) {
+                    sequences.remove(&sequence);
+                }
+
+                drop(sequences);
+
+                callback(TouchPoint { id, x, y, phase });
+
+                // Pinch and swipe gestures still need the touches
+                Propagation::Proceed
+            }
+        ));
+
+        self.add_controller(event_controller_legacy);
+    }
+
+    /// Pinch gestures, the callback receives the scale change since the last update
+    pub fn connect_pinch<T: Fn(f64) + 'static>(&self, callback: T) {
+        let gesture_zoom = GestureZoom::new();
+        let last_scale = Rc::new(Cell::new(1.0));
+
+        gesture_zoom.connect_begin(clone!(
+            #[strong]
+            last_scale,
+            move |_, _| {
+                last_scale.set(1.0);
+            }
+        ));
+
+        gesture_zoom.connect_scale_changed(move |_, scale| {
+            if scale > 0.0 {
+                callback(scale / last_scale.get());
+                last_scale.set(scale);
+            }
+        });
+
+        self.add_controller(gesture_zoom);
+    }
+
+    /// Horizontal touchpad swipes, the callback receives `true` to go back
+    pub fn connect_swipe<T: Fn(bool) + 'static>(&self, callback: T) {
+        let gesture_swipe = GestureSwipe::builder().n_points(SWIPE_FINGERS).build();
+
+        gesture_swipe.connect_swipe(move |_, velocity_x, velocity_y| {
+            if velocity_x.abs() >= SWIPE_MIN_VELOCITY && velocity_x.abs() > velocity_y.abs() * 2.0 {
+                callback(velocity_x > 0.0);
+            }
+        });
+
+        self.add_controller(gesture_swipe);
+    }
+
     pub fn connect_keys<T: Fn(Rc<KeyboardState>) + 'static>(&self, callback: T) {
         let callback = Rc::new(callback);
         let event_controller_key = EventControllerKey::new();
 
         let pressed_callback = callback.clone();
         let kayboard_state_pressed = self.imp().keyboard_state.clone();
-        event_controller_key.connect_key_pressed(move |_, key, code, modifiers| {
-            let character = key.to_unicode();
-            kayboard_state_pressed.set_character(character);
+        event_controller_key.connect_key_pressed(move |controller, key, code, modifiers| {
+            let (filtered, committed) = filter_keypress(controller);
+            let text = if filtered {
+                committed
+            } else {
+                key.to_unicode().map(String::from)
+            };
+
+            kayboard_state_pressed.set_key(key);
+            kayboard_state_pressed.set_composing(filtered && text.is_none());
+            kayboard_state_pressed.set_text(text);
             kayboard_state_pressed.set_pressed(true);
             kayboard_state_pressed.set_code(code);
             kayboard_state_pressed.set_modifiers(modifiers);
+            kayboard_state_pressed.set_num_lock(num_lock_state(controller));
 
             pressed_callback(kayboard_state_pressed.clone());
 
@@ -159,12 +368,16 @@ impl WebView {
 
         let released_callback = callback.clone();
         let kayboard_state_released = self.imp().keyboard_state.clone();
-        event_controller_key.connect_key_released(move |_, key, code, modifiers| {
-            let character = key.to_unicode();
-            kayboard_state_released.set_character(character);
+        event_controller_key.connect_key_released(move |controller, key, code, modifiers| {
+            let (filtered, _) = filter_keypress(controller);
+
+            kayboard_state_released.set_key(key);
+            kayboard_state_released.set_composing(filtered);
+            kayboard_state_released.set_text(None);
             kayboard_state_released.set_pressed(false);
             kayboard_state_released.set_code(code);
             kayboard_state_released.set_modifiers(modifiers);
+            kayboard_state_released.set_num_lock(num_lock_state(controller));
 
             released_callback(kayboard_state_released.clone());
         });
@@ -172,34 +385,178 @@ impl WebView {
         self.add_controller(event_controller_key);
     }
 
-    pub fn connect_clipboard<T: Fn(String) + 'static>(&self, callback: T) {
-        let callback = Rc::new(callback);
-        let event_controller_key = EventControllerKey::new();
+    pub fn connect_ime_preedit<T: Fn(String, i32) + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "ime-preedit",
+            false,
+            glib::closure_local!(move |_: WebView, text: String, cursor: i32| {
+                callback(text, cursor);
+            }),
+        );
+    }
+
+    pub fn connect_ime_commit<T: Fn(String) + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "ime-commit",
+            false,
+            glib::closure_local!(move |_: WebView, text: String| {
+                callback(text);
+            }),
+        );
+    }
 
-        event_controller_key.connect_key_pressed(move |_, key, _, modifiers| {
-            let ctrl_modifier = modifiers.contains(ModifierType::CONTROL_MASK);
+    pub fn set_ime_cursor_location(&self, x: i32, y: i32, width: i32, height: i32) {
+        let area = gdk::Rectangle::new(x, y, width, height);
+        self.imp().im_context.set_cursor_location(&area);
+    }
 
-            if ctrl_modifier && key == Key::v {
-                if let Some(display) = Display::default() {
-                    let clipboard = display.clipboard();
+    pub fn set_selection(&self, text: String) {
+        // Selecting text makes it available for middle click paste, like any other GTK app
+        if !text.is_empty() {
+            self.display().primary_clipboard().set_text(&text);
+        }
 
-                    let callback = callback.clone();
-                    clipboard.read_text_async(None::<&Cancellable>, move |result| {
-                        if let Ok(Some(text)) = result {
-                            callback(text.to_string());
+        self.imp().selection.replace(text);
+    }
+
+    /// Copies the current page selection to the clipboard
+    pub fn copy_selection(&self) -> bool {
+        let selection = self.imp().selection.borrow();
+
+        if selection.is_empty() {
+            return false;
+        }
+
+        // The browser can not reach the system clipboard on every setup
+        self.display().clipboard().set_text(&selection);
+
+        true
+    }
+
+    pub fn popup_menu(&self, model: &impl IsA<gio::MenuModel>, x: i32, y: i32) {
+        let popover = PopoverMenu::from_model(Some(model));
+        popover.set_parent(self);
+        popover.set_has_arrow(false);
+        popover.set_halign(Align::Start);
+        popover.set_pointing_to(Some(&gdk::Rectangle::new(x, y, 1, 1)));
+
+        // Unparenting right away would drop the action of the clicked item
+        popover.connect_closed(|popover| {
+            glib::idle_add_local_once(clone!(
+                #[weak]
+                popover,
+                move || popover.unparent()
+            ));
+        });
+
+        popover.popup();
+    }
+
+    pub fn set_browser_cursor(&self, cursor: BrowserCursor) {
+        let cursor = match cursor {
+            BrowserCursor::Named(name) => gdk::Cursor::from_name(name, None),
+            BrowserCursor::Custom {
+                width,
+                height,
+                hotspot_x,
+                hotspot_y,
+                pixels,
+            } => {
+                let bytes = glib::Bytes::from_owned(pixels);
+                let texture = gdk::MemoryTexture::new(
+                    width,
+                    height,
+                    gdk::MemoryFormat::B8g8r8a8Premultiplied,
+                    &bytes,
+                    width as usize * 4,
+                );
+
+                Some(gdk::Cursor::from_texture(
+                    &texture, hotspot_x, hotspot_y, None,
+                ))
+            }
+            BrowserCursor::Hidden => gdk::Cursor::from_name("none", None),
+        };
+
+        self.set_cursor(cursor.as_ref());
+    }
+
+    /// Handles the clipboard shortcuts instead of the page, pastes come with the text of the
+    /// system clipboard if it holds any
+    pub fn connect_clipboard<T: Fn(ClipboardAction, Option<String>) + 'static>(&self, callback: T) {
+        let callback = Rc::new(callback);
+        let event_controller_key = EventControllerKey::new();
+        // Runs before the key is forwarded, so the browser never handles it a second time
+        event_controller_key.set_propagation_phase(PropagationPhase::Capture);
+
+        event_controller_key.connect_key_pressed(clone!(
+            #[weak(rename_to = webview)]
+            self,
+            #[upgrade_or]
+            Propagation::Proceed,
+            move |_, key, _, modifiers| {
+                let Some(action) = clipboard_action(key, modifiers) else {
+                    return Propagation::Proceed;
+                };
+
+                match action {
+                    ClipboardAction::Copy | ClipboardAction::Cut => {
+                        // Nothing to copy, let the page handle the shortcut
+                        if !webview.copy_selection() {
+                            return Propagation::Proceed;
                         }
-                    });
+
+                        callback(action, None);
+                    }
+                    ClipboardAction::Paste | ClipboardAction::PasteAndMatchStyle => {
+                        let callback = callback.clone();
+                        webview.read_clipboard(move |text| callback(action, text));
+                    }
                 }
 
-                return Propagation::Stop;
+                Propagation::Stop
             }
-
-            Propagation::Proceed
-        });
+        ));
 
         self.add_controller(event_controller_key);
     }
 
+    /// Reads the text of the system clipboard, which the browser can not reach on every setup
+    pub fn read_clipboard<T: FnOnce(Option<String>) + 'static>(&self, callback: T) {
+        self.display()
+            .clipboard()
+            .read_text_async(None::<&Cancellable>, move |result| {
+                callback(result.ok().flatten().map(|text| text.to_string()));
+            });
+    }
+
+    pub fn connect_primary_paste<T: Fn(Rc<PointerState>, String) + 'static>(&self, callback: T) {
+        let callback = Rc::new(callback);
+        let gesture_click = GestureClick::builder().button(gdk::BUTTON_MIDDLE).build();
+        let pointer_state = self.imp().pointer_state.clone();
+
+        gesture_click.connect_pressed(clone!(
+            #[weak(rename_to = webview)]
+            self,
+            move |_, _, x, y| {
+                pointer_state.set_position(x, y);
+
+                let pointer_state = pointer_state.clone();
+                let callback = callback.clone();
+                webview.display().primary_clipboard().read_text_async(
+                    None::<&Cancellable>,
+                    move |result| {
+                        if let Ok(Some(text)) = result {
+                            callback(pointer_state, text.to_string());
+                        }
+                    },
+                );
+            }
+        ));
+
+        self.add_controller(gesture_click);
+    }
+
     pub fn connect_file_enter<F: Fn(Rc<PointerState>, PathBuf) + 'static>(&self, callback: F) {
         if let Some(drop_target) = self.controller::<DropTarget>() {
             let callback = Rc::new(callback);
@@ -264,6 +621,18 @@ impl WebView {
         }
     }
 
+    /// Position of an event relative to the widget, events report it relative to the surface
+    fn event_position(&self, event: &gdk::Event) -> Option<(f64, f64)> {
+        let (x, y) = event.position()?;
+        let native = self.native()?;
+        let (surface_x, surface_y) = native.surface_transform();
+
+        let point = graphene::Point::new((x - surface_x) as f32, (y - surface_y) as f32);
+        let point = native.compute_point(self, &point)?;
+
+        Some((point.x() as f64, point.y() as f64))
+    }
+
     fn controller<T: IsA<Object>>(&self) -> Option<T> {
         for controller in &self.observe_controllers() {
             if let Ok(controller) = controller
@@ -276,3 +645,53 @@ impl WebView {
         None
     }
 }
+
+fn clipboard_action(key: Key, modifiers: ModifierType) -> Option<ClipboardAction> {
+    let control = modifiers.contains(ModifierType::CONTROL_MASK);
+    let shift = modifiers.contains(ModifierType::SHIFT_MASK);
+
+    match key.to_lower() {
+        Key::c if control && !shift => Some(ClipboardAction::Copy),
+        Key::Insert if control && !shift => Some(ClipboardAction::Copy),
+        Key::x if control && !shift => Some(ClipboardAction::Cut),
+        Key::Delete if shift && !control => Some(ClipboardAction::Cut),
+        Key::v if control && shift => Some(ClipboardAction::PasteAndMatchStyle),
+        Key::v if control => Some(ClipboardAction::Paste),
+        Key::Insert if shift && !control => Some(ClipboardAction::Paste),
+        _ => None,
+    }
+}
+
+fn is_touch(gesture: &GestureClick) -> bool {
+    gesture.current_event().is_some_and(|event| {
+        matches!(
+            event.event_type(),
+            EventType::TouchBegin | EventType::TouchUpdate | EventType::TouchEnd
+        )
+    })
+}
+
+/// Lets the input method handle the key event, returning whether it was consumed
+/// and the text it committed while doing so
+fn filter_keypress(controller: &EventControllerKey) -> (bool, Option<String>) {
+    let Some(webview) = controller.widget().and_downcast::<WebView>() else {
+        return (false, None);
+    };
+
+    let Some(event) = controller.current_event() else {
+        return (false, None);
+    };
+
+    let imp = webview.imp();
+    imp.im_key_press.set(true);
+    let filtered = imp.im_context.filter_keypress(&event);
+    imp.im_key_press.set(false);
+
+    (filtered, imp.im_commit.take())
+}
+
+fn num_lock_state(controller: &EventControllerKey) -> bool {
+    controller
+        .current_event_device()
+        .is_some_and(|device| device.num_lock_state())
+}
@@ -0,0 +1,6 @@
+/// Width of the window when entering the mini player
+pub const MINI_PLAYER_WIDTH: i32 = 480;
+/// Smallest width the mini player can be resized to
+pub const MINI_PLAYER_MIN_WIDTH: i32 = 240;
+/// Aspect ratio used until the video reports its own
+pub const DEFAULT_ASPECT_RATIO: f64 = 16.0 / 9.0;
@@ -1,26 +1,60 @@
-use std::{cell::Cell, sync::Arc};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+};
 
 use adw::prelude::*;
 use adw::subclass::prelude::*;
 use ashpd::{
     WindowIdentifier,
     desktop::{
-        Request,
+        Request, ResponseError,
         background::Background,
+        file_chooser::{FileFilter, SelectedFiles},
         inhibit::{InhibitFlags, InhibitProxy},
         open_uri::OpenFileRequest,
     },
     enumflags2::BitFlags,
 };
+use gettextrs::gettext;
 use gtk::{
-    glib::{self, clone, subclass::InitializingObject},
+    glib::{
+        self, clone,
+        subclass::{InitializingObject, Signal},
+    },
     prelude::WidgetExt,
 };
 use tokio::sync::Mutex;
-use tracing::error;
+use tracing::{error, warn};
 use url::Url;
 
-use crate::spawn_local;
+use crate::{
+    app::window::{
+        config::{DEFAULT_ASPECT_RATIO, MINI_PLAYER_MIN_WIDTH, MINI_PLAYER_WIDTH},
+        stacking,
+    },
+    chromium::{
+        CertificateError, CertificateProblem, Download, DownloadRequest, DownloadState, FileDialog,
+        FileDialogMode, PermissionRequest,
+    },
+    settings::CloseAction,
+    shared::types::Permission,
+    spawn_local,
+};
+
+/// Window state saved when entering the mini player
+#[derive(Debug, Clone, Copy)]
+pub struct MiniPlayerState {
+    pub width: i32,
+    pub height: i32,
+    pub min_width: i32,
+    pub min_height: i32,
+    pub decorated: bool,
+    pub maximized: bool,
+    pub fullscreen: bool,
+}
 
 #[derive(Default, glib::Properties, gtk::CompositeTemplate)]
 #[properties(wrapper_type = super::Window)]
@@ -31,10 +65,36 @@ pub struct Window {
     #[template_child]
     pub header: TemplateChild<adw::HeaderBar>,
     #[template_child]
+    pub toast_overlay: TemplateChild<adw::ToastOverlay>,
+    #[template_child]
     pub overlay: TemplateChild<gtk::Overlay>,
     #[template_child]
     pub fps_label: TemplateChild<gtk::Label>,
+    #[template_child]
+    pub status_label: TemplateChild<gtk::Label>,
+    #[template_child]
+    pub mini_player: TemplateChild<gtk::WindowHandle>,
+    #[template_child]
+    pub mini_controls: TemplateChild<gtk::Revealer>,
+    #[template_child]
+    pub play_pause_button: TemplateChild<gtk::Button>,
+    pub webview_offload: RefCell<Option<gtk::GraphicsOffload>>,
+    pub mini_player_state: Cell<Option<MiniPlayerState>>,
+    pub video_size: Cell<Option<(i32, i32)>>,
+    /// Address of the page shown, titles fall back to its host
+    pub page_address: RefCell<String>,
+    pub aspect_locked: Cell<bool>,
+    /// Set while the window is resized to the aspect ratio, so it doesn't react to itself
+    pub aspect_locking: Cell<bool>,
+    pub close_action: Cell<CloseAction>,
+    pub tray_available: Cell<bool>,
     pub inhibit_request: Arc<Mutex<Option<Request<()>>>>,
+    /// Permission requests waiting for the dialog of their origin
+    pub pending_permissions: RefCell<HashMap<String, Vec<PermissionRequest>>>,
+    /// Certificate errors waiting for the dialog of their host and certificate
+    pub pending_certificates: RefCell<HashMap<(String, Option<String>), Vec<CertificateError>>>,
+    /// Running downloads with their progress toast
+    pub downloads: RefCell<HashMap<u32, (adw::Toast, Download)>>,
 }
 
 impl Window {
@@ -99,6 +159,410 @@ impl Window {
         });
     }
 
+    fn close(&self, action: CloseAction) {
+        let object = self.obj();
+
+        match action {
+            CloseAction::HideToTray => object.set_visible(false),
+            CloseAction::Quit => {
+                if let Some(application) = object.application() {
+                    application.quit();
+                }
+            }
+            CloseAction::Ask => self.ask_close_action(),
+        }
+    }
+
+    fn ask_close_action(&self) {
+        let object = self.obj();
+        let tray_available = self.tray_available.get();
+
+        let remember = gtk::CheckButton::with_label(&gettext("Remember my choice"));
+        let dialog = adw::AlertDialog::builder()
+            .heading(gettext("Quit Stremio?"))
+            .body(gettext(
+                "Stremio can keep running in the background and be reopened from the tray",
+            ))
+            .extra_child(&remember)
+            .close_response("cancel")
+            .default_response("quit")
+            .build();
+
+        dialog.add_response("cancel", &gettext("_Cancel"));
+        if tray_available {
+            dialog.add_response("hide", &gettext("_Hide to Tray"));
+        }
+        dialog.add_response("quit", &gettext("_Quit"));
+        dialog.set_response_appearance("quit", adw::ResponseAppearance::Destructive);
+
+        dialog.connect_response(
+            None,
+            clone!(
+                #[weak]
+                object,
+                move |_, response| {
+                    let action = match response {
+                        "hide" => CloseAction::HideToTray,
+                        "quit" => CloseAction::Quit,
+                        _ => return,
+                    };
+
+                    if remember.is_active() {
+                        object.imp().close_action.set(action);
+                        object.emit_by_name::<()>("close-action-changed", &[&action.position()]);
+                    }
+
+                    object.imp().close(action);
+                }
+            ),
+        );
+
+        dialog.present(Some(&*object));
+    }
+
+    pub fn ask_certificate_error(&self, error: CertificateError) {
+        let object = self.obj();
+        let key = (error.host.clone(), error.fingerprint.clone());
+
+        // Pages load many resources from a host, answer them all with a single dialog
+        if let Some(pending) = self.pending_certificates.borrow_mut().get_mut(&key) {
+            pending.push(error);
+            return;
+        }
+
+        let problem = match error.problem {
+            CertificateProblem::CommonNameInvalid => {
+                gettext("The certificate was issued for a different website")
+            }
+            CertificateProblem::DateInvalid => {
+                gettext("The certificate has expired or is not yet valid")
+            }
+            CertificateProblem::AuthorityInvalid => {
+                gettext("The certificate is not issued by a trusted authority")
+            }
+            CertificateProblem::Revoked => gettext("The certificate has been revoked"),
+            CertificateProblem::Invalid => gettext("The certificate is invalid"),
+        };
+
+        let mut details = vec![problem];
+        if let Some(subject) = &error.subject {
+            details.push(format!("{}: {subject}", gettext("Issued to")));
+        }
+        if let Some(issuer) = &error.issuer {
+            details.push(format!("{}: {issuer}", gettext("Issued by")));
+        }
+
+        // Choices are remembered for a certificate, not for any the host may show later
+        let remember = gtk::CheckButton::with_label(&gettext("Remember my choice"));
+        remember.set_visible(error.fingerprint.is_some());
+
+        let dialog = adw::AlertDialog::builder()
+            .heading(format!(
+                "{} {}",
+                gettext("Connection not secure:"),
+                error.host
+            ))
+            .body(details.join("\n"))
+            .extra_child(&remember)
+            .close_response("deny")
+            .default_response("deny")
+            .build();
+
+        dialog.add_response("deny", &gettext("_Go Back"));
+        dialog.add_response("allow", &gettext("_Continue Anyway"));
+        dialog.set_response_appearance("allow", adw::ResponseAppearance::Destructive);
+
+        self.pending_certificates
+            .borrow_mut()
+            .insert(key.clone(), vec![error]);
+
+        dialog.connect_response(
+            None,
+            clone!(
+                #[weak]
+                object,
+                move |_, response| {
+                    let Some(errors) = object.imp().pending_certificates.borrow_mut().remove(&key)
+                    else {
+                        return;
+                    };
+
+                    let allowed = response == "allow";
+                    if remember.is_active()
+                        && let (host, Some(fingerprint)) = &key
+                    {
+                        object.emit_by_name::<()>(
+                            "certificate-decision",
+                            &[host, fingerprint, &allowed],
+                        );
+                    }
+
+                    for error in errors {
+                        if allowed {
+                            error.allow();
+                        } else {
+                            error.deny();
+                        }
+                    }
+                }
+            ),
+        );
+
+        dialog.present(Some(&*object));
+    }
+
+    pub fn ask_permission(&self, request: PermissionRequest) {
+        let object = self.obj();
+
+        // Only one dialog per origin, later requests wait for its answer
+        if let Some(pending) = self
+            .pending_permissions
+            .borrow_mut()
+            .get_mut(&request.origin)
+        {
+            pending.push(request);
+            return;
+        }
+
+        let permissions: Vec<String> = request
+            .permissions
+            .iter()
+            .map(|permission| format!("• {}", permission_label(*permission)))
+            .collect();
+
+        let remember = gtk::CheckButton::with_label(&gettext("Remember my choice"));
+        let dialog = adw::AlertDialog::builder()
+            .heading(gettext("Allow Access?"))
+            .body(format!(
+                "{} {}\n\n{}",
+                request.origin,
+                gettext("wants to use:"),
+                permissions.join("\n")
+            ))
+            .extra_child(&remember)
+            .close_response("deny")
+            .default_response("deny")
+            .build();
+
+        dialog.add_response("deny", &gettext("_Deny"));
+        dialog.add_response("allow", &gettext("_Allow"));
+        dialog.set_response_appearance("allow", adw::ResponseAppearance::Suggested);
+
+        let origin = request.origin.clone();
+        let asked = request.permissions.clone();
+        self.pending_permissions
+            .borrow_mut()
+            .insert(origin.clone(), vec![request]);
+
+        dialog.connect_response(
+            None,
+            clone!(
+                #[weak]
+                object,
+                move |_, response| {
+                    let Some(requests) = object
+                        .imp()
+                        .pending_permissions
+                        .borrow_mut()
+                        .remove(&origin)
+                    else {
+                        return;
+                    };
+
+                    let granted = response == "allow";
+                    if remember.is_active() {
+                        for permission in &asked {
+                            object.emit_by_name::<()>(
+                                "permission-decision",
+                                &[&origin, permission, &granted],
+                            );
+                        }
+                    }
+
+                    for request in requests {
+                        // Requests asking for more than what was shown get a dialog of their own
+                        if !request.permissions.iter().all(|it| asked.contains(it)) {
+                            object.imp().ask_permission(request);
+                        } else if granted {
+                            request.grant();
+                        } else {
+                            request.deny();
+                        }
+                    }
+                }
+            ),
+        );
+
+        dialog.present(Some(&*object));
+    }
+
+    /// Shows only the video in a small borderless window, kept above other windows on X11
+    ///
+    /// Wayland leaves stacking to the compositor, where always on top is set from the window
+    /// menu, e.g. with Super+Space on GNOME or Alt+F3 on KDE.
+    pub fn set_mini_player(&self, active: bool) {
+        let object = self.obj();
+
+        if active == self.mini_player_state.get().is_some() {
+            return;
+        }
+
+        if active {
+            let (width, height) = object.default_size();
+            let (min_width, min_height) = object.size_request();
+
+            self.mini_player_state.set(Some(MiniPlayerState {
+                width,
+                height,
+                min_width,
+                min_height,
+                decorated: object.is_decorated(),
+                maximized: object.is_maximized(),
+                fullscreen: object.is_fullscreen(),
+            }));
+
+            object.unfullscreen();
+            object.unmaximize();
+            object.set_decorated(false);
+            self.set_keep_above(true);
+            self.header.set_visible(false);
+            self.set_webview_visible(false);
+            self.mini_player.set_visible(true);
+
+            let aspect = self.aspect_ratio();
+            object.set_size_request(
+                MINI_PLAYER_MIN_WIDTH,
+                (MINI_PLAYER_MIN_WIDTH as f64 / aspect).round() as i32,
+            );
+            object.set_default_size(
+                MINI_PLAYER_WIDTH,
+                (MINI_PLAYER_WIDTH as f64 / aspect).round() as i32,
+            );
+        } else if let Some(state) = self.mini_player_state.take() {
+            self.mini_player.set_visible(false);
+            self.set_webview_visible(true);
+            self.set_keep_above(false);
+            object.set_decorated(state.decorated);
+            self.header
+                .set_visible(self.decorations.get() && !state.fullscreen);
+
+            object.set_size_request(state.min_width, state.min_height);
+            object.set_default_size(state.width, state.height);
+
+            if state.maximized {
+                object.maximize();
+            }
+
+            if state.fullscreen {
+                object.fullscreen();
+            }
+        }
+    }
+
+    fn set_keep_above(&self, above: bool) {
+        let object = self.obj();
+        if !stacking::can_keep_above(&object.display()) {
+            return;
+        }
+
+        if let Some(surface) = object.surface()
+            && let Err(e) = stacking::set_keep_above(&surface, above)
+        {
+            warn!("Failed to keep the mini player above other windows: {e}");
+        }
+    }
+
+    pub fn set_video_size(&self, size: Option<(i32, i32)>) {
+        self.video_size
+            .set(size.filter(|(width, height)| *width > 0 && *height > 0));
+        self.lock_aspect_ratio(gtk::Orientation::Horizontal);
+    }
+
+    pub fn set_aspect_locked(&self, locked: bool) {
+        self.aspect_locked.set(locked);
+        self.lock_aspect_ratio(gtk::Orientation::Horizontal);
+    }
+
+    pub fn aspect_ratio(&self) -> f64 {
+        match self.video_size.get() {
+            Some((width, height)) => width as f64 / height as f64,
+            None => DEFAULT_ASPECT_RATIO,
+        }
+    }
+
+    /// Resizes the window so the video is shown at the given percentage of its native size
+    pub fn fit_to_video(&self, percentage: i32) {
+        let object = self.obj();
+
+        let Some((width, height)) = self.video_size.get() else {
+            return;
+        };
+
+        if object.is_fullscreen() {
+            return;
+        }
+
+        object.unmaximize();
+
+        // Video sizes are in physical pixels while the window is sized in logical ones
+        let scale = percentage as f64 / 100.0 / object.scale_factor() as f64;
+        let width = (width as f64 * scale).round() as i32;
+        let height = (height as f64 * scale).round() as i32;
+
+        object.set_default_size(width, height + self.header_height());
+    }
+
+    fn header_height(&self) -> i32 {
+        if self.header.is_visible() {
+            self.header.height()
+        } else {
+            0
+        }
+    }
+
+    /// Keeps the window in the video aspect ratio, following the side that was resized
+    fn lock_aspect_ratio(&self, resized: gtk::Orientation) {
+        let object = self.obj();
+
+        if self.aspect_locking.get() {
+            return;
+        }
+
+        let mini_player = self.mini_player_state.get().is_some();
+        let playing_locked = self.aspect_locked.get() && self.video_size.get().is_some();
+
+        if !(mini_player || playing_locked) || object.is_maximized() || object.is_fullscreen() {
+            return;
+        }
+
+        let (width, height) = object.default_size();
+        let header_height = self.header_height();
+        let aspect = self.aspect_ratio();
+
+        let locked_size = match resized {
+            gtk::Orientation::Vertical => (
+                ((height - header_height) as f64 * aspect).round() as i32,
+                height,
+            ),
+            _ => (
+                width,
+                (width as f64 / aspect).round() as i32 + header_height,
+            ),
+        };
+
+        if locked_size != (width, height) {
+            self.aspect_locking.set(true);
+            object.set_default_size(locked_size.0, locked_size.1);
+            self.aspect_locking.set(false);
+        }
+    }
+
+    fn set_webview_visible(&self, visible: bool) {
+        if let Some(offload) = self.webview_offload.borrow().as_ref() {
+            offload.set_visible(visible);
+        }
+    }
+
     pub fn open_uri(&self, uri: Url) {
         let object = self.obj();
 
@@ -118,6 +582,283 @@ impl Window {
             }
         ));
     }
+
+    pub fn choose_files(&self, dialog: FileDialog) {
+        let object = self.obj().downgrade();
+
+        spawn_local!(async move {
+            // The page waits for an answer, even when the window is gone
+            let Some(object) = object.upgrade() else {
+                dialog.cancel();
+                return;
+            };
+
+            let identifier = WindowIdentifier::from_native(&object).await;
+
+            match select_files(identifier, &dialog).await {
+                Ok(paths) => dialog.select(paths),
+                Err(e) => {
+                    if !matches!(e, ashpd::Error::Response(ResponseError::Cancelled)) {
+                        error!("Failed to choose files: {e}");
+                    }
+
+                    dialog.cancel();
+                }
+            }
+        });
+    }
+
+    pub fn choose_download_path(&self, request: DownloadRequest) {
+        let object = self.obj();
+
+        spawn_local!(clone!(
+            #[weak]
+            object,
+            async move {
+                let identifier = WindowIdentifier::from_native(&object).await;
+                let downloads_dir = downloads_dir();
+
+                match select_download_path(identifier, &downloads_dir, &request.suggested_name)
+                    .await
+                {
+                    Ok(path) => request.accept(&path),
+                    // Dropping the request cancels the download
+                    Err(ashpd::Error::Response(ResponseError::Cancelled)) => {}
+                    Err(e) => {
+                        error!("Failed to choose download location: {e}");
+
+                        let path = unique_path(&downloads_dir, &request.suggested_name);
+                        request.accept(&path);
+                    }
+                }
+            }
+        ));
+    }
+
+    pub fn show_proxy_auth_failed(&self, host: &str) {
+        let title = gettext("Proxy {} refused the username and password").replace("{}", host);
+        let toast = adw::Toast::builder().title(title).use_markup(false).build();
+
+        self.toast_overlay.add_toast(toast);
+    }
+
+    pub fn update_download(&self, download: Download) {
+        let name = download.file_name().unwrap_or_default();
+        let mut downloads = self.downloads.borrow_mut();
+
+        match download.state {
+            DownloadState::InProgress => {
+                let title = match download.fraction() {
+                    Some(fraction) => format!(
+                        "{}: {name} ({:.0}%)",
+                        gettext("Downloading"),
+                        fraction * 100.0
+                    ),
+                    None => format!("{}: {name}", gettext("Downloading")),
+                };
+
+                if let Some((toast, current)) = downloads.get_mut(&download.id) {
+                    toast.set_title(&title);
+                    *current = download;
+                } else {
+                    let toast = adw::Toast::builder()
+                        .title(title)
+                        .use_markup(false)
+                        .timeout(0)
+                        .button_label(gettext("_Cancel"))
+                        .build();
+
+                    let window = self.obj();
+                    let id = download.id;
+                    toast.connect_button_clicked(clone!(
+                        #[weak]
+                        window,
+                        move |_| {
+                            if let Some((_, download)) = window.imp().downloads.borrow().get(&id) {
+                                download.cancel();
+                            }
+                        }
+                    ));
+
+                    self.toast_overlay.add_toast(toast.clone());
+                    downloads.insert(download.id, (toast, download));
+                }
+            }
+            state => {
+                if let Some((toast, _)) = downloads.remove(&download.id) {
+                    toast.dismiss();
+                }
+
+                let title = match state {
+                    DownloadState::Complete => format!("{}: {name}", gettext("Downloaded")),
+                    DownloadState::Interrupted => {
+                        format!("{}: {name}", gettext("Download failed"))
+                    }
+                    _ => return,
+                };
+
+                let toast = adw::Toast::builder().title(title).use_markup(false).build();
+
+                self.toast_overlay.add_toast(toast);
+            }
+        }
+    }
+}
+
+fn permission_label(permission: Permission) -> String {
+    match permission {
+        Permission::Camera => gettext("Camera"),
+        Permission::Microphone => gettext("Microphone"),
+        Permission::ScreenCapture => gettext("Screen sharing"),
+        Permission::Notifications => gettext("Notifications"),
+        Permission::Clipboard => gettext("Clipboard"),
+        Permission::Geolocation => gettext("Location"),
+        Permission::MultipleDownloads => gettext("Multiple downloads"),
+        Permission::LocalFonts => gettext("Installed fonts"),
+        Permission::PointerLock => gettext("Mouse pointer lock"),
+        Permission::KeyboardLock => gettext("Keyboard lock"),
+        Permission::IdleDetection => gettext("Idle detection"),
+        Permission::WindowManagement => gettext("Window management"),
+        Permission::StorageAccess => gettext("Storage access"),
+        Permission::ProtectedMedia => gettext("Protected content"),
+        Permission::LocalNetworkAccess => gettext("Local network"),
+        Permission::Other => gettext("Other permissions"),
+    }
+}
+
+async fn select_download_path(
+    identifier: Option<WindowIdentifier>,
+    downloads_dir: &Path,
+    suggested_name: &str,
+) -> ashpd::Result<PathBuf> {
+    let request = SelectedFiles::save_file()
+        .identifier(identifier)
+        .modal(true)
+        .current_name(suggested_name)
+        .current_folder(Some(downloads_dir).filter(|dir| dir.is_dir()))?
+        .send()
+        .await?;
+
+    request
+        .response()?
+        .uris()
+        .first()
+        .and_then(|uri| uri.to_file_path().ok())
+        .ok_or(ashpd::Error::Response(ResponseError::Other))
+}
+
+fn downloads_dir() -> PathBuf {
+    glib::user_special_dir(glib::UserDirectory::Downloads)
+        .unwrap_or_else(|| glib::home_dir().join("Downloads"))
+}
+
+/// Path in `dir` for `name` that does not overwrite an existing file
+fn unique_path(dir: &Path, name: &str) -> PathBuf {
+    let path = dir.join(name);
+    if !path.exists() {
+        return path;
+    }
+
+    let name = Path::new(name);
+    let stem = name
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let extension = name
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+
+    (1..)
+        .map(|index| dir.join(format!("{stem} ({index}){extension}")))
+        .find(|path| !path.exists())
+        .unwrap_or(path)
+}
+
+async fn select_files(
+    identifier: Option<WindowIdentifier>,
+    dialog: &FileDialog,
+) -> ashpd::Result<Vec<PathBuf>> {
+    let title = dialog.title.as_deref();
+    let filters = file_filters(&dialog.filters);
+
+    let request = match dialog.mode {
+        FileDialogMode::Save => {
+            let name = dialog
+                .default_path
+                .as_ref()
+                .and_then(|path| path.file_name())
+                .and_then(|name| name.to_str());
+
+            let folder = dialog
+                .default_path
+                .as_ref()
+                .and_then(|path| path.parent())
+                .filter(|folder| folder.is_dir());
+
+            SelectedFiles::save_file()
+                .identifier(identifier)
+                .title(title)
+                .modal(true)
+                .current_name(name)
+                .current_folder(folder)?
+                .filters(filters)
+                .send()
+                .await?
+        }
+        mode => {
+            let folder = dialog.default_path.as_ref().filter(|path| path.is_dir());
+
+            SelectedFiles::open_file()
+                .identifier(identifier)
+                .title(title)
+                .modal(true)
+                .multiple(mode == FileDialogMode::OpenMultiple)
+                .directory(mode == FileDialogMode::OpenFolder)
+                .current_folder(folder)?
+                .filters(filters)
+                .send()
+                .await?
+        }
+    };
+
+    let paths = request
+        .response()?
+        .uris()
+        .iter()
+        .filter_map(|uri| uri.to_file_path().ok())
+        .collect();
+
+    Ok(paths)
+}
+
+/// Converts the page accept filters to portal filters
+///
+/// Filters are MIME types (`image/*`), extensions (`.png`)
+/// or described groups of extensions (`Images|.png;.jpg`).
+fn file_filters(filters: &[String]) -> Vec<FileFilter> {
+    filters
+        .iter()
+        .filter_map(|filter| {
+            let (label, patterns) = filter
+                .split_once('|')
+                .unwrap_or((filter.as_str(), filter.as_str()));
+
+            let mut file_filter = FileFilter::new(label);
+            let mut empty = true;
+
+            for pattern in patterns.split(';').map(str::trim) {
+                file_filter = match pattern.strip_prefix('.') {
+                    Some(extension) => file_filter.glob(&format!("*.{extension}")),
+                    None if pattern.contains('/') => file_filter.mimetype(pattern),
+                    None => continue,
+                };
+                empty = false;
+            }
+
+            (!empty).then_some(file_filter)
+        })
+        .collect()
 }
 
 #[glib::object_subclass]
@@ -137,12 +878,68 @@ impl ObjectSubclass for Window {
 
 #[glib::derived_properties]
 impl ObjectImpl for Window {
+    fn signals() -> &'static [Signal] {
+        static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+        SIGNALS.get_or_init(|| {
+            vec![
+                Signal::builder("close-action-changed")
+                    .param_types([u32::static_type()])
+                    .build(),
+                Signal::builder("permission-decision")
+                    .param_types([
+                        String::static_type(),
+                        Permission::static_type(),
+                        bool::static_type(),
+                    ])
+                    .build(),
+                Signal::builder("certificate-decision")
+                    .param_types([
+                        String::static_type(),
+                        String::static_type(),
+                        bool::static_type(),
+                    ])
+                    .build(),
+            ]
+        })
+    }
+
     fn constructed(&self) {
         self.parent_constructed();
 
+        let object = self.obj();
+
         if cfg!(debug_assertions) {
-            self.obj().add_css_class("devel");
+            object.add_css_class("devel");
         }
+
+        // The tray host reports late, assume it is there until it says otherwise
+        self.tray_available.set(true);
+
+        object.connect_default_width_notify(|window| {
+            window.imp().lock_aspect_ratio(gtk::Orientation::Horizontal);
+        });
+
+        object.connect_default_height_notify(|window| {
+            window.imp().lock_aspect_ratio(gtk::Orientation::Vertical);
+        });
+
+        let controls = self.mini_controls.get();
+        let motion_controller = gtk::EventControllerMotion::new();
+        motion_controller.connect_enter(clone!(
+            #[weak]
+            controls,
+            move |_, _, _| {
+                controls.set_reveal_child(true);
+            }
+        ));
+        motion_controller.connect_leave(clone!(
+            #[weak]
+            controls,
+            move |_| {
+                controls.set_reveal_child(false);
+            }
+        ));
+        self.mini_player.add_controller(motion_controller);
     }
 }
 
@@ -168,8 +965,13 @@ impl WindowImpl for Window {
     fn close_request(&self) -> glib::Propagation {
         self.parent_close_request();
 
-        let widget = self.obj();
-        widget.set_visible(false);
+        // Hiding without a tray host would leave no way to bring the window back
+        let action = match self.close_action.get() {
+            CloseAction::HideToTray if !self.tray_available.get() => CloseAction::Quit,
+            action => action,
+        };
+
+        self.close(action);
 
         glib::Propagation::Stop
     }
@@ -1,19 +1,31 @@
+mod config;
 mod imp;
+mod stacking;
+
+use std::{cell::Cell, rc::Rc};
 
 use adw::subclass::prelude::*;
 use gtk::{
-    Widget,
+    Widget, gdk,
     gdk::prelude::{DisplayExt, MonitorExt},
-    gio,
+    gio::{
+        self,
+        prelude::{ListModelExt, ListModelExtManual},
+    },
     glib::{
         self,
         object::{IsA, ObjectExt},
     },
-    prelude::{GtkWindowExt, NativeExt, WidgetExt},
+    prelude::{ButtonExt, GtkWindowExt, NativeExt, WidgetExt},
 };
 use url::Url;
 
-use crate::app::Application;
+use crate::{
+    app::{Application, config::APP_NAME},
+    chromium::{CertificateError, Download, DownloadRequest, FileDialog, PermissionRequest},
+    settings::{CloseAction, WindowSettings},
+    shared::types::Permission,
+};
 
 glib::wrapper! {
     pub struct Window(ObjectSubclass<imp::Window>)
@@ -42,6 +54,8 @@ impl Window {
         window
             .fps_label
             .insert_after(&*window.overlay, Some(&offload));
+
+        window.webview_offload.replace(Some(offload));
     }
 
     pub fn set_fullscreen(&self, fullscreen: bool) {
@@ -51,6 +65,107 @@ impl Window {
         self.set_fullscreened(fullscreen);
     }
 
+    pub fn restore_state(&self, settings: &WindowSettings) {
+        self.set_default_size(settings.width, settings.height);
+
+        if settings.maximized {
+            self.maximize();
+        }
+
+        let monitor = settings
+            .monitor
+            .as_deref()
+            .and_then(|connector| find_monitor(&self.display(), connector));
+
+        if settings.fullscreen {
+            match monitor {
+                Some(monitor) => {
+                    self.imp().header.set_visible(false);
+                    self.fullscreen_on_monitor(&monitor);
+                }
+                None => self.set_fullscreen(true),
+            }
+        } else if let Some(monitor) = monitor.filter(|_| self.display().monitors().n_items() > 1) {
+            // GTK 4 can't place windows, so the window opens fullscreen on the monitor and
+            // leaves it once shown, most compositors keep it there but the final place is theirs
+            self.fullscreen_on_monitor(&monitor);
+
+            let handler = Rc::new(Cell::new(None));
+            let id = self.connect_map(glib::clone!(
+                #[strong]
+                handler,
+                move |window| {
+                    if let Some(id) = handler.take() {
+                        window.disconnect(id);
+                    }
+
+                    glib::idle_add_local_once(glib::clone!(
+                        #[weak]
+                        window,
+                        move || window.unfullscreen()
+                    ));
+                }
+            ));
+            handler.set(Some(id));
+        }
+    }
+
+    pub fn store_state(&self, settings: &mut WindowSettings) {
+        // The default size follows the unmaximized size of the window
+        let (width, height) = self.default_size();
+        settings.width = width;
+        settings.height = height;
+        settings.maximized = self.is_maximized();
+        settings.fullscreen = self.is_fullscreen();
+
+        // Reopen as the window the mini player was entered from
+        if let Some(state) = self.imp().mini_player_state.get() {
+            settings.width = state.width;
+            settings.height = state.height;
+            settings.maximized = state.maximized;
+            settings.fullscreen = state.fullscreen;
+        }
+
+        // Hidden windows have no surface, keep the last known monitor
+        if let Some(connector) = self
+            .surface()
+            .and_then(|surface| self.display().monitor_at_surface(&surface))
+            .and_then(|monitor| monitor.connector())
+        {
+            settings.monitor = Some(connector.to_string());
+        }
+    }
+
+    pub fn set_mini_player(&self, active: bool) {
+        self.imp().set_mini_player(active);
+    }
+
+    pub fn is_mini_player(&self) -> bool {
+        self.imp().mini_player_state.get().is_some()
+    }
+
+    pub fn set_video_size(&self, size: Option<(i32, i32)>) {
+        self.imp().set_video_size(size);
+    }
+
+    pub fn set_aspect_locked(&self, locked: bool) {
+        self.imp().set_aspect_locked(locked);
+    }
+
+    pub fn fit_to_video(&self, percentage: i32) {
+        self.imp().fit_to_video(percentage);
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        let icon_name = if paused {
+            "media-playback-start-symbolic"
+        } else {
+            "media-playback-pause-symbolic"
+        };
+
+        self.imp().play_pause_button.set_icon_name(icon_name);
+    }
+
     pub fn connect_monitor_info<T: Fn(f64, i32, f64) + 'static>(&self, callback: T) {
         let callback = std::rc::Rc::new(callback);
 
@@ -123,6 +238,59 @@ impl Window {
         );
     }
 
+    pub fn set_close_action(&self, action: CloseAction) {
+        self.imp().close_action.set(action);
+    }
+
+    pub fn set_tray_available(&self, available: bool) {
+        self.imp().tray_available.set(available);
+
+        // Hidden while the tray was still assumed, there is no other way back
+        if !available && !self.is_visible() {
+            self.present();
+        }
+    }
+
+    pub fn connect_close_action_changed<T: Fn(CloseAction) + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "close-action-changed",
+            false,
+            glib::closure_local!(move |_: Window, position: u32| {
+                callback(CloseAction::from_position(position));
+            }),
+        );
+    }
+
+    pub fn connect_permission_decision<T: Fn(String, Permission, bool) + 'static>(
+        &self,
+        callback: T,
+    ) {
+        self.connect_closure(
+            "permission-decision",
+            false,
+            glib::closure_local!(move |_: Window,
+                                       origin: String,
+                                       permission: Permission,
+                                       granted: bool| {
+                callback(origin, permission, granted);
+            }),
+        );
+    }
+
+    /// Called with the host, the certificate fingerprint and whether it was allowed
+    pub fn connect_certificate_decision<T: Fn(String, String, bool) + 'static>(&self, callback: T) {
+        self.connect_closure(
+            "certificate-decision",
+            false,
+            glib::closure_local!(move |_: Window,
+                                       host: String,
+                                       fingerprint: String,
+                                       allowed: bool| {
+                callback(host, fingerprint, allowed);
+            }),
+        );
+    }
+
     pub fn connect_visibility<T: Fn(bool) + 'static>(&self, callback: T) {
         self.connect_visible_notify(move |window| {
             callback(window.is_visible());
@@ -145,6 +313,63 @@ impl Window {
         self.imp().open_uri(uri);
     }
 
+    /// Asks whether to grant the permissions a page requested
+    pub fn ask_permission(&self, request: PermissionRequest) {
+        self.imp().ask_permission(request);
+    }
+
+    /// Asks whether to continue to a host with an invalid certificate,
+    /// once for all the requests waiting on the same certificate
+    pub fn ask_certificate_error(&self, error: CertificateError) {
+        self.imp().ask_certificate_error(error);
+    }
+
+    /// Asks where to save a download, falling back to the downloads folder
+    pub fn choose_download_path(&self, request: DownloadRequest) {
+        self.imp().choose_download_path(request);
+    }
+
+    pub fn update_download(&self, download: Download) {
+        self.imp().update_download(download);
+    }
+
+    /// Tells that the proxy refused the credentials from the settings
+    pub fn show_proxy_auth_failed(&self, host: &str) {
+        self.imp().show_proxy_auth_failed(host);
+    }
+
+    /// Shows a file chooser for the page and answers it with the selected paths
+    pub fn choose_files(&self, dialog: FileDialog) {
+        self.imp().choose_files(dialog);
+    }
+
+    pub fn set_page_title(&self, title: &str) {
+        if !title.is_empty() {
+            self.set_title(Some(title));
+            return;
+        }
+
+        // Untitled websites show their host, like browsers do
+        let host = Url::parse(&self.imp().page_address.borrow())
+            .ok()
+            .filter(|url| matches!(url.scheme(), "http" | "https"))
+            .and_then(|url| url.host_str().map(str::to_owned));
+
+        self.set_title(Some(host.as_deref().unwrap_or(APP_NAME)));
+    }
+
+    /// Remembers the address of the page, the title that follows may depend on it
+    pub fn set_page_address(&self, address: &str) {
+        self.imp().page_address.replace(address.to_owned());
+    }
+
+    /// Shows the page status text, like the target of a hovered link
+    pub fn set_status(&self, status: &str) {
+        let label = &self.imp().status_label;
+        label.set_label(status);
+        label.set_visible(!status.is_empty());
+    }
+
     pub fn set_fps_visible(&self, visible: bool) {
         self.imp().fps_label.set_visible(visible);
     }
@@ -161,6 +386,14 @@ impl Window {
     }
 }
 
+fn find_monitor(display: &gdk::Display, connector: &str) -> Option<gdk::Monitor> {
+    display
+        .monitors()
+        .iter::<gdk::Monitor>()
+        .flatten()
+        .find(|monitor| monitor.connector().as_deref() == Some(connector))
+}
+
 fn graphics_offload(widget: &impl IsA<Widget>) -> gtk::GraphicsOffload {
     gtk::GraphicsOffload::builder()
         .vexpand(true)
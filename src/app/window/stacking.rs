@@ -0,0 +1,72 @@
+use std::ffi::{c_ulong, c_void};
+
+use anyhow::Context;
+use gtk::{
+    gdk,
+    glib::object::{ObjectExt, ObjectType},
+};
+use libloading::os::unix::Library;
+use x11rb::{
+    connection::Connection,
+    protocol::xproto::{ClientMessageEvent, ConnectionExt, EventMask},
+};
+
+const X11_DISPLAY: &str = "GdkX11Display";
+/// Actions of `_NET_WM_STATE` client messages
+const NET_WM_STATE_REMOVE: u32 = 0;
+const NET_WM_STATE_ADD: u32 = 1;
+/// Tells the window manager the request comes from an application
+const SOURCE_APPLICATION: u32 = 1;
+
+type SurfaceXid = unsafe extern "C" fn(*mut c_void) -> c_ulong;
+
+/// Whether windows can be kept above others, GTK 4 has no api for it and only X11 window
+/// managers take the request from applications, Wayland compositors offer it in their own
+/// window menu instead
+pub fn can_keep_above(display: &gdk::Display) -> bool {
+    display.type_().name() == X11_DISPLAY
+}
+
+/// Asks the window manager to keep the surface above other windows, per EWMH
+pub fn set_keep_above(surface: &gdk::Surface, above: bool) -> anyhow::Result<()> {
+    // Looked up at runtime, GTK may be built without X11 support
+    let library = Library::this();
+    let surface_xid = unsafe { library.get::<SurfaceXid>(b"gdk_x11_surface_get_xid") }
+        .context("GTK has no X11 support")?;
+    // SAFETY: only called for surfaces of an X11 display, which are GdkX11Surfaces
+    let window = unsafe { surface_xid(surface.as_ptr() as *mut c_void) } as u32;
+
+    let (connection, screen) = x11rb::connect(None)?;
+    let root = connection.setup().roots[screen].root;
+
+    let state = connection
+        .intern_atom(false, b"_NET_WM_STATE")?
+        .reply()?
+        .atom;
+    let state_above = connection
+        .intern_atom(false, b"_NET_WM_STATE_ABOVE")?
+        .reply()?
+        .atom;
+
+    let action = if above {
+        NET_WM_STATE_ADD
+    } else {
+        NET_WM_STATE_REMOVE
+    };
+    let event = ClientMessageEvent::new(
+        32,
+        window,
+        state,
+        [action, state_above, 0, SOURCE_APPLICATION, 0],
+    );
+
+    connection.send_event(
+        false,
+        root,
+        EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+        event,
+    )?;
+    connection.flush()?;
+
+    Ok(())
+}
@@ -1,11 +1,23 @@
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex, RwLock};
 
-use cef::sys::cef_state_t::STATE_ENABLED;
 use cef::{rc::*, *};
 use flume::Sender;
+use tracing::error;
 
-use crate::chromium::{ChromiumEvent, app::client::ChromiumClient, types::Viewport};
-use crate::shared::pbo_manager::{BufferPool, PboManager};
+use crate::chromium::{
+    ChromiumEvent,
+    app::{
+        client::{ChromiumClient, browser_settings, set_proxy_credentials, window_info},
+        scheme_handler::{Bundle, ChromiumSchemeHandlerFactory, Loader},
+    },
+    types::Viewport,
+};
+use crate::config::{WEB_UI_HOST, WEB_UI_SCHEME};
+use crate::shared::{
+    pbo_manager::{BufferPool, PboManager},
+    proxy::Proxy,
+};
 
 wrap_browser_process_handler! {
     pub struct ChromiumBrowserProcessHandler {
@@ -14,31 +26,54 @@ wrap_browser_process_handler! {
         sender: Sender<ChromiumEvent>,
         pbo_manager: Arc<PboManager>,
         buffer_pool: Arc<BufferPool>,
+        proxy: Arc<RwLock<Proxy>>,
+        web_ui: Option<PathBuf>,
     }
 
     impl BrowserProcessHandler {
         fn on_context_initialized(&self) {
+            if let Ok(proxy) = self.proxy.read() {
+                set_proxy_preference(&proxy);
+
+                // Chromium leaves credentials out of the preference and asks for them instead
+                if let Some((username, password)) = proxy.credentials() {
+                    set_proxy_credentials(username, password);
+                }
+            }
+
+            if let Some(path) = &self.web_ui {
+                match Bundle::open(path) {
+                    Ok(bundle) => {
+                        let mut factory = ChromiumSchemeHandlerFactory::new(Arc::new(Loader::spawn(bundle)));
+                        let registered = register_scheme_handler_factory(
+                            Some(&CefString::from(WEB_UI_SCHEME)),
+                            Some(&CefString::from(WEB_UI_HOST)),
+                            Some(&mut factory),
+                        );
+
+                        if registered != 1 {
+                            error!("Failed to serve the web UI from {}", path.display());
+                        }
+                    }
+                    Err(e) => error!("Failed to open the web UI bundle {}: {e}", path.display()),
+                }
+            }
+
             let mut client = ChromiumClient::new(
+                self.browser.clone(),
                 self.viewport.clone(),
                 self.sender.clone(),
 
                 self.pbo_manager.clone(),
                 self.buffer_pool.clone(),
+                true,
+                Default::default(),
+                Default::default(),
             );
             let url = CefString::from("about:blank");
 
-            let window_info = WindowInfo {
-                windowless_rendering_enabled: true.into(),
-                shared_texture_enabled: false.into(),
-                ..Default::default()
-            };
-
-            let settings = BrowserSettings {
-                windowless_frame_rate: crate::chromium::config::MAX_FRAME_RATE as i32,
-                javascript_access_clipboard: STATE_ENABLED.into(),
-                javascript_dom_paste: STATE_ENABLED.into(),
-                ..Default::default()
-            };
+            let window_info = window_info();
+            let settings = browser_settings();
 
             let browser_result = browser_host_create_browser_sync(
                 Some(&window_info),
@@ -55,3 +90,49 @@ wrap_browser_process_handler! {
         }
     }
 }
+
+/// Applies the proxy to the global request context, using Chromium's proxy preference format
+fn set_proxy_preference(proxy: &Proxy) {
+    let (Some(context), Some(mut dictionary), Some(mut value)) = (
+        request_context_get_global_context(),
+        dictionary_value_create(),
+        value_create(),
+    ) else {
+        return;
+    };
+
+    let mut set = |key: &str, value: &str| {
+        dictionary.set_string(Some(&CefString::from(key)), Some(&CefString::from(value)));
+    };
+
+    match proxy {
+        Proxy::System => set("mode", "system"),
+        Proxy::Direct => set("mode", "direct"),
+        Proxy::Server(url) => {
+            let host = url.host_str().unwrap_or_default();
+            let server = match url.port_or_known_default() {
+                Some(port) => format!("{}://{host}:{port}", url.scheme()),
+                None => format!("{}://{host}", url.scheme()),
+            };
+
+            set("mode", "fixed_servers");
+            set("server", &server);
+        }
+        Proxy::Pac(url) => {
+            set("mode", "pac_script");
+            set("pac_url", url.as_str());
+        }
+    }
+
+    value.set_dictionary(Some(&mut dictionary));
+
+    let mut error = CefString::default();
+    if context.set_preference(
+        Some(&CefString::from("proxy")),
+        Some(&mut value),
+        Some(&mut error),
+    ) != 1
+    {
+        error!("Failed to set the proxy preference: {error}");
+    }
+}
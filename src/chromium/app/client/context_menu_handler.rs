@@ -0,0 +1,39 @@
+use cef::{rc::*, *};
+use flume::Sender;
+
+use crate::chromium::{ChromiumEvent, types::ContextMenu};
+
+wrap_context_menu_handler! {
+    pub struct ChromiumContextMenuHandler {
+        sender: Sender<ChromiumEvent>,
+    }
+
+    impl ContextMenuHandler {
+        fn on_before_context_menu(
+            &self,
+            _browser: Option<&mut Browser>,
+            _frame: Option<&mut Frame>,
+            params: Option<&mut ContextMenuParams>,
+            model: Option<&mut MenuModel>,
+        ) {
+            // The menu is built natively, an empty model keeps CEF from showing its own
+            if let Some(model) = model {
+                model.clear();
+            }
+
+            if let Some(params) = params {
+                let link = CefString::from(&params.link_url()).to_string();
+
+                let context_menu = ContextMenu {
+                    x: params.xcoord(),
+                    y: params.ycoord(),
+                    link: (!link.is_empty()).then_some(link),
+                    selection: CefString::from(&params.selection_text()).to_string(),
+                    editable: params.is_editable() == 1,
+                };
+
+                self.sender.send(ChromiumEvent::ContextMenu(context_menu)).ok();
+            }
+        }
+    }
+}
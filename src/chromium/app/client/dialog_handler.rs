@@ -0,0 +1,92 @@
+use std::path::PathBuf;
+
+use cef::sys::cef_file_dialog_mode_t::{self, *};
+use cef::{rc::*, *};
+use flume::Sender;
+
+use crate::chromium::{
+    ChromiumEvent,
+    types::{FileDialog, FileDialogMode},
+};
+
+wrap_dialog_handler! {
+    pub struct ChromiumDialogHandler {
+        sender: Sender<ChromiumEvent>,
+    }
+
+    impl DialogHandler {
+        fn on_file_dialog(
+            &self,
+            _browser: Option<&mut Browser>,
+            mode: cef::FileDialogMode,
+            title: Option<&CefString>,
+            default_file_path: Option<&CefString>,
+            accept_filters: Option<&mut CefStringList>,
+            accept_extensions: Option<&mut CefStringList>,
+            accept_descriptions: Option<&mut CefStringList>,
+            callback: Option<&mut FileDialogCallback>,
+        ) -> i32 {
+            let Some(callback) = callback else {
+                return false.into();
+            };
+
+            let mode = match cef_file_dialog_mode_t::from(mode) {
+                FILE_DIALOG_OPEN_MULTIPLE => FileDialogMode::OpenMultiple,
+                FILE_DIALOG_OPEN_FOLDER => FileDialogMode::OpenFolder,
+                FILE_DIALOG_SAVE => FileDialogMode::Save,
+                _ => FileDialogMode::Open,
+            };
+
+            let title = title
+                .map(|title| title.to_string())
+                .filter(|title| !title.is_empty());
+
+            let default_path = default_file_path
+                .map(|path| path.to_string())
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from);
+
+            let extensions = strings(accept_extensions);
+            let descriptions = strings(accept_descriptions);
+            let filters = strings(accept_filters)
+                .iter()
+                .enumerate()
+                .map(|(index, filter)| {
+                    accept_filter(
+                        filter,
+                        extensions.get(index).map(String::as_str),
+                        descriptions.get(index).map(String::as_str),
+                    )
+                })
+                .collect();
+
+            let file_dialog = FileDialog::new(mode, title, default_path, filters, callback.clone());
+            self.sender.send(ChromiumEvent::FileDialog(file_dialog)).ok();
+
+            // The dialog is answered asynchronously through the callback
+            true.into()
+        }
+    }
+}
+
+fn strings(list: Option<&mut CefStringList>) -> Vec<String> {
+    list.map(|list| list.iter().map(|string| string.to_string()).collect())
+        .unwrap_or_default()
+}
+
+/// Joins a filter with the extensions and description CEF found for it,
+/// into a `Description|.ext1;.ext2` group
+fn accept_filter(filter: &str, extensions: Option<&str>, description: Option<&str>) -> String {
+    if filter.contains('|') {
+        return filter.to_owned();
+    }
+
+    let label = description
+        .filter(|description| !description.is_empty())
+        .unwrap_or(filter);
+
+    match extensions.filter(|extensions| !extensions.is_empty()) {
+        Some(extensions) => format!("{label}|{filter};{extensions}"),
+        None => format!("{label}|{filter}"),
+    }
+}
@@ -1,8 +1,20 @@
+use cef::sys::{
+    cef_cursor_type_t::{self, *},
+    cef_log_severity_t::{self, *},
+};
 use cef::{rc::*, *};
 use flume::Sender;
 
-use crate::chromium::ChromiumEvent;
+use crate::{
+    chromium::{
+        ChromiumEvent,
+        types::{ConsoleLevel, ConsoleMessage},
+    },
+    shared::types::BrowserCursor,
+};
 
+// Favicons are not forwarded: GTK 4 windows only take icon names from the theme,
+// so there is nowhere to show a page icon
 wrap_display_handler! {
     pub struct ChromiumDisplayHandler {
         sender: Sender<ChromiumEvent>,
@@ -18,5 +30,150 @@ wrap_display_handler! {
 
             self.sender.send(ChromiumEvent::Fullscreen(state)).ok();
         }
+
+        fn on_address_change(
+            &self,
+            _browser: Option<&mut Browser>,
+            frame: Option<&mut Frame>,
+            url: Option<&CefString>,
+        ) {
+            if let Some(frame) = frame
+                && frame.is_main() == 1
+                && let Some(url) = url
+            {
+                self.sender.send(ChromiumEvent::Address(url.to_string())).ok();
+            }
+        }
+
+        fn on_title_change(&self, _browser: Option<&mut Browser>, title: Option<&CefString>) {
+            let title = title.map(|title| title.to_string()).unwrap_or_default();
+            self.sender.send(ChromiumEvent::Title(title)).ok();
+        }
+
+        fn on_status_message(&self, _browser: Option<&mut Browser>, value: Option<&CefString>) {
+            let status = value.map(|value| value.to_string()).unwrap_or_default();
+            self.sender.send(ChromiumEvent::Status(status)).ok();
+        }
+
+        fn on_console_message(
+            &self,
+            _browser: Option<&mut Browser>,
+            level: LogSeverity,
+            message: Option<&CefString>,
+            source: Option<&CefString>,
+            line: i32,
+        ) -> i32 {
+            let level = match cef_log_severity_t::from(level) {
+                LOGSEVERITY_VERBOSE => ConsoleLevel::Debug,
+                LOGSEVERITY_WARNING => ConsoleLevel::Warning,
+                LOGSEVERITY_ERROR | LOGSEVERITY_FATAL => ConsoleLevel::Error,
+                _ => ConsoleLevel::Info,
+            };
+
+            let console_message = ConsoleMessage {
+                level,
+                message: message.map(|message| message.to_string()).unwrap_or_default(),
+                source: source.map(|source| source.to_string()).unwrap_or_default(),
+                line,
+            };
+
+            self.sender.send(ChromiumEvent::Console(console_message)).ok();
+
+            // Already logged through tracing, skip the CEF log file
+            true.into()
+        }
+
+        fn on_cursor_change(
+            &self,
+            _browser: Option<&mut Browser>,
+            _cursor: CursorHandle,
+            type_: CursorType,
+            custom_cursor_info: Option<&CursorInfo>,
+        ) -> i32 {
+            let cursor = match cef_cursor_type_t::from(type_) {
+                CT_NONE => BrowserCursor::Hidden,
+                CT_CUSTOM => custom_cursor_info
+                    .and_then(custom_cursor)
+                    .unwrap_or(BrowserCursor::Named("default")),
+                cursor_type => BrowserCursor::Named(cursor_name(cursor_type)),
+            };
+
+            self.sender.send(ChromiumEvent::Cursor(cursor)).ok();
+
+            true.into()
+        }
+    }
+}
+
+/// Copies the bitmap out of CEF, the buffer is only valid during the callback
+fn custom_cursor(info: &CursorInfo) -> Option<BrowserCursor> {
+    let (width, height) = (info.size.width, info.size.height);
+
+    if info.buffer.is_null() || width <= 0 || height <= 0 {
+        return None;
+    }
+
+    let length = (width as usize)
+        .checked_mul(height as usize)?
+        .checked_mul(4)?;
+    // SAFETY: CEF gives a BGRA bitmap of `size` with 4 bytes per pixel and no row padding,
+    // so `buffer` holds `length` bytes, alive until the callback returns
+    let pixels = unsafe { std::slice::from_raw_parts(info.buffer as *const u8, length) }.to_vec();
+
+    Some(BrowserCursor::Custom {
+        width,
+        height,
+        hotspot_x: info.hotspot.x,
+        hotspot_y: info.hotspot.y,
+        pixels,
+    })
+}
+
+fn cursor_name(cursor_type: cef_cursor_type_t) -> &'static str {
+    match cursor_type {
+        CT_CROSS => "crosshair",
+        CT_HAND => "pointer",
+        CT_IBEAM => "text",
+        CT_WAIT => "wait",
+        CT_HELP => "help",
+        CT_EASTRESIZE => "e-resize",
+        CT_NORTHRESIZE => "n-resize",
+        CT_NORTHEASTRESIZE => "ne-resize",
+        CT_NORTHWESTRESIZE => "nw-resize",
+        CT_SOUTHRESIZE => "s-resize",
+        CT_SOUTHEASTRESIZE => "se-resize",
+        CT_SOUTHWESTRESIZE => "sw-resize",
+        CT_WESTRESIZE => "w-resize",
+        CT_NORTHSOUTHRESIZE => "ns-resize",
+        CT_EASTWESTRESIZE => "ew-resize",
+        CT_NORTHEASTSOUTHWESTRESIZE => "nesw-resize",
+        CT_NORTHWESTSOUTHEASTRESIZE => "nwse-resize",
+        CT_COLUMNRESIZE => "col-resize",
+        CT_ROWRESIZE => "row-resize",
+        CT_MIDDLEPANNING | CT_MIDDLE_PANNING_VERTICAL | CT_MIDDLE_PANNING_HORIZONTAL => {
+            "all-scroll"
+        }
+        CT_EASTPANNING => "e-resize",
+        CT_NORTHPANNING => "n-resize",
+        CT_NORTHEASTPANNING => "ne-resize",
+        CT_NORTHWESTPANNING => "nw-resize",
+        CT_SOUTHPANNING => "s-resize",
+        CT_SOUTHEASTPANNING => "se-resize",
+        CT_SOUTHWESTPANNING => "sw-resize",
+        CT_WESTPANNING => "w-resize",
+        CT_MOVE => "move",
+        CT_VERTICALTEXT => "vertical-text",
+        CT_CELL => "cell",
+        CT_CONTEXTMENU => "context-menu",
+        CT_ALIAS | CT_DND_LINK => "alias",
+        CT_PROGRESS => "progress",
+        CT_NODROP | CT_DND_NONE => "no-drop",
+        CT_COPY | CT_DND_COPY => "copy",
+        CT_NOTALLOWED => "not-allowed",
+        CT_ZOOMIN => "zoom-in",
+        CT_ZOOMOUT => "zoom-out",
+        CT_GRAB | CT_DND_MOVE => "grab",
+        CT_GRABBING => "grabbing",
+        _ => "default",
     }
 }
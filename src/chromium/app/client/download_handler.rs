@@ -0,0 +1,75 @@
+use std::path::PathBuf;
+
+use cef::{rc::*, *};
+use flume::Sender;
+
+use crate::chromium::{
+    ChromiumEvent,
+    types::{Download, DownloadRequest, DownloadState},
+};
+
+wrap_download_handler! {
+    pub struct ChromiumDownloadHandler {
+        sender: Sender<ChromiumEvent>,
+    }
+
+    impl DownloadHandler {
+        fn on_before_download(
+            &self,
+            _browser: Option<&mut Browser>,
+            download_item: Option<&mut DownloadItem>,
+            suggested_name: Option<&CefString>,
+            callback: Option<&mut BeforeDownloadCallback>,
+        ) -> i32 {
+            let (Some(download_item), Some(callback)) = (download_item, callback) else {
+                return false.into();
+            };
+
+            let suggested_name = suggested_name
+                .map(|name| name.to_string())
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| CefString::from(&download_item.suggested_file_name()).to_string());
+
+            let request = DownloadRequest::new(download_item.id(), suggested_name, callback.clone());
+            self.sender.send(ChromiumEvent::DownloadRequest(request)).ok();
+
+            // The destination is chosen asynchronously through the callback
+            true.into()
+        }
+
+        fn on_download_updated(
+            &self,
+            _browser: Option<&mut Browser>,
+            download_item: Option<&mut DownloadItem>,
+            callback: Option<&mut DownloadItemCallback>,
+        ) {
+            let (Some(download_item), Some(callback)) = (download_item, callback) else {
+                return;
+            };
+
+            let state = if download_item.is_complete() == 1 {
+                DownloadState::Complete
+            } else if download_item.is_canceled() == 1 {
+                DownloadState::Canceled
+            } else if download_item.is_interrupted() == 1 {
+                DownloadState::Interrupted
+            } else {
+                DownloadState::InProgress
+            };
+
+            let path = CefString::from(&download_item.full_path()).to_string();
+            let path = (!path.is_empty()).then(|| PathBuf::from(path));
+
+            let download = Download::new(
+                download_item.id(),
+                path,
+                download_item.received_bytes(),
+                download_item.total_bytes(),
+                state,
+                callback.clone(),
+            );
+
+            self.sender.send(ChromiumEvent::Download(download)).ok();
+        }
+    }
+}
@@ -1,11 +1,13 @@
+use std::sync::{Arc, Mutex};
+
 use cef::{rc::*, *};
 use flume::Sender;
-use url::Url;
 
-use crate::chromium::ChromiumEvent;
+use crate::chromium::{ChromiumEvent, popup::PopupPolicy};
 
 wrap_life_span_handler! {
     pub struct ChromiumLifeSpanHandler {
+        browser: Arc<Mutex<Option<Browser>>>,
         sender: Sender<ChromiumEvent>,
     }
 
@@ -27,14 +29,36 @@ wrap_life_span_handler! {
             _no_javascript_access: Option<&mut i32>,
         ) -> i32 {
             if let Some(target_url) = target_url {
-                let target_url = target_url.to_string();
+                let event = match PopupPolicy::for_url(&target_url.to_string()) {
+                    PopupPolicy::External(url) => ChromiumEvent::Open(url),
+                    PopupPolicy::Auxiliary(url) => ChromiumEvent::OpenAuxiliary(url),
+                    PopupPolicy::Internal(url) => ChromiumEvent::Deeplink(url.to_string()),
+                    PopupPolicy::Blocked => return true.into(),
+                };
 
-                if let Ok(url) = Url::parse(&target_url) {
-                    self.sender.send(ChromiumEvent::Open(url)).ok();
-                }
+                self.sender.send(event).ok();
             }
 
+            // Popups never open as off-screen browsers of their own
             true.into()
         }
+
+        fn on_after_created(&self, browser: Option<&mut Browser>) {
+            // Browsers created asynchronously are only known from here
+            if let Some(browser) = browser
+                && let Ok(mut current) = self.browser.lock()
+                && current.is_none()
+            {
+                *current = Some(browser.clone());
+            }
+        }
+
+        fn on_before_close(&self, _browser: Option<&mut Browser>) {
+            if let Ok(mut browser) = self.browser.lock() {
+                browser.take();
+            }
+
+            self.sender.send(ChromiumEvent::Closed).ok();
+        }
     }
 }
@@ -1,17 +1,22 @@
 use cef::{rc::*, *};
 use flume::Sender;
-use std::sync::Mutex;
+use std::{
+    sync::{Arc, Mutex},
+    time::Instant,
+};
 
 use crate::chromium::{
     ChromiumEvent,
     config::{IPC_RECEIVER, IPC_SCRIPT, IPC_SENDER},
 };
 
-static NAV_START: Mutex<Option<std::time::Instant>> = Mutex::new(None);
-
 wrap_load_handler! {
     pub struct ChromiumLoadHandler {
         sender: Sender<ChromiumEvent>,
+        /// Only our own page gets the IPC script, never the third-party pages of logins
+        inject_ipc: bool,
+        /// Start of the main frame navigation of this browser
+        navigation_start: Arc<Mutex<Option<Instant>>>,
     }
 
     impl LoadHandler {
@@ -33,9 +38,14 @@ wrap_load_handler! {
 
                 if is_main {
                     // Track navigation start time
-                    if let Ok(mut nav) = NAV_START.lock() {
-                        *nav = Some(std::time::Instant::now());
+                    if let Ok(mut nav) = self.navigation_start.lock() {
+                        *nav = Some(Instant::now());
                     }
+
+                    if !self.inject_ipc {
+                        return;
+                    }
+
                     let script = IPC_SCRIPT
                         .replace("IPC_SENDER", IPC_SENDER)
                         .replace("IPC_RECEIVER", IPC_RECEIVER);
@@ -63,7 +73,7 @@ wrap_load_handler! {
 
                 if is_main {
                     // Calculate navigation duration
-                    if let Ok(mut nav) = NAV_START.lock() && let Some(start) = nav.take() {
+                    if let Ok(mut nav) = self.navigation_start.lock() && let Some(start) = nav.take() {
                         let duration = start.elapsed();
                         if duration.as_secs() > 1 {
                             tracing::error!("NAVIGATION SLOW: {:?} for status={}", duration, http_status_code);
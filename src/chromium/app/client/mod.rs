@@ -1,33 +1,70 @@
+mod context_menu_handler;
+mod dialog_handler;
 mod display_handler;
+mod download_handler;
 mod lifespan_handler;
 mod load_handler;
 mod permission_handler;
 mod render_handler;
+mod request_handler;
 
-use std::sync::{Arc, RwLock};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, RwLock},
+    time::Instant,
+};
 
+use cef::sys::cef_state_t::STATE_ENABLED;
 use cef::{rc::*, *};
 use flume::Sender;
 
 use crate::chromium::{
     ChromiumEvent,
     app::client::{
-        display_handler::ChromiumDisplayHandler, lifespan_handler::ChromiumLifeSpanHandler,
-        load_handler::ChromiumLoadHandler, permission_handler::ChromiumPermissionHandler,
+        context_menu_handler::ChromiumContextMenuHandler, dialog_handler::ChromiumDialogHandler,
+        display_handler::ChromiumDisplayHandler, download_handler::ChromiumDownloadHandler,
+        lifespan_handler::ChromiumLifeSpanHandler, load_handler::ChromiumLoadHandler,
+        permission_handler::ChromiumPermissionHandler,
     },
-    config::{IPC_MESSAGE, READY_MESSAGE},
+    config::{IPC_MESSAGE, MAX_FRAME_RATE, READY_MESSAGE},
     types::Viewport,
 };
 use crate::shared::pbo_manager::{BufferPool, PboManager};
 use render_handler::ChromiumRenderHandler;
+use request_handler::ChromiumRequestHandler;
+
+pub use request_handler::set_proxy_credentials;
+
+/// Off-screen rendering into our own frames, shared by every browser we create
+pub fn window_info() -> WindowInfo {
+    WindowInfo {
+        windowless_rendering_enabled: true.into(),
+        shared_texture_enabled: false.into(),
+        ..Default::default()
+    }
+}
+
+pub fn browser_settings() -> BrowserSettings {
+    BrowserSettings {
+        windowless_frame_rate: MAX_FRAME_RATE as i32,
+        javascript_access_clipboard: STATE_ENABLED.into(),
+        javascript_dom_paste: STATE_ENABLED.into(),
+        ..Default::default()
+    }
+}
 
 wrap_client! {
     pub struct ChromiumClient {
+        browser: Arc<Mutex<Option<Browser>>>,
         viewport: Arc<RwLock<Viewport>>,
         sender: Sender<ChromiumEvent>,
 
         pbo_manager: Arc<PboManager>,
         buffer_pool: Arc<BufferPool>,
+
+        inject_ipc: bool,
+        navigation_start: Arc<Mutex<Option<Instant>>>,
+        proxy_attempts: Arc<Mutex<HashMap<String, usize>>>,
     }
 
     impl Client {
@@ -42,21 +79,47 @@ wrap_client! {
         }
 
         fn load_handler(&self) -> Option<LoadHandler> {
-            Some(ChromiumLoadHandler::new(self.sender.clone()))
+            Some(ChromiumLoadHandler::new(
+                self.sender.clone(),
+                self.inject_ipc,
+                self.navigation_start.clone(),
+            ))
         }
 
         fn life_span_handler(&self) -> Option<LifeSpanHandler> {
-            Some(ChromiumLifeSpanHandler::new(self.sender.clone()))
+            Some(ChromiumLifeSpanHandler::new(
+                self.browser.clone(),
+                self.sender.clone(),
+            ))
         }
 
         fn permission_handler(&self) -> Option<PermissionHandler> {
-            Some(ChromiumPermissionHandler::new())
+            Some(ChromiumPermissionHandler::new(self.sender.clone()))
+        }
+
+        fn request_handler(&self) -> Option<RequestHandler> {
+            Some(ChromiumRequestHandler::new(
+                self.sender.clone(),
+                self.proxy_attempts.clone(),
+            ))
         }
 
         fn display_handler(&self) -> Option<DisplayHandler> {
             Some(ChromiumDisplayHandler::new(self.sender.clone()))
         }
 
+        fn dialog_handler(&self) -> Option<DialogHandler> {
+            Some(ChromiumDialogHandler::new(self.sender.clone()))
+        }
+
+        fn download_handler(&self) -> Option<DownloadHandler> {
+            Some(ChromiumDownloadHandler::new(self.sender.clone()))
+        }
+
+        fn context_menu_handler(&self) -> Option<ContextMenuHandler> {
+            Some(ChromiumContextMenuHandler::new(self.sender.clone()))
+        }
+
         fn on_process_message_received(
             &self,
             _browser: Option<&mut Browser>,
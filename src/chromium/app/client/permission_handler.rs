@@ -1,29 +1,241 @@
-use cef::sys::{
-    cef_permission_request_result_t::CEF_PERMISSION_RESULT_ACCEPT,
-    cef_permission_request_types_t::CEF_PERMISSION_TYPE_LOCAL_NETWORK_ACCESS,
-};
+use cef::sys::{cef_media_access_permission_types_t::*, cef_permission_request_types_t::*};
 use cef::{rc::*, *};
+use flume::Sender;
+
+use crate::{
+    chromium::{
+        ChromiumEvent,
+        types::{PermissionCallback, PermissionRequest},
+    },
+    shared::types::Permission,
+};
+
+/// Permission prompt bits with a dedicated permission
+const PROMPT_PERMISSIONS: &[(u32, Permission)] = &[
+    (CEF_PERMISSION_TYPE_CAMERA_STREAM as u32, Permission::Camera),
+    (
+        CEF_PERMISSION_TYPE_MIC_STREAM as u32,
+        Permission::Microphone,
+    ),
+    (
+        CEF_PERMISSION_TYPE_NOTIFICATIONS as u32,
+        Permission::Notifications,
+    ),
+    (CEF_PERMISSION_TYPE_CLIPBOARD as u32, Permission::Clipboard),
+    (
+        CEF_PERMISSION_TYPE_GEOLOCATION as u32,
+        Permission::Geolocation,
+    ),
+    (
+        CEF_PERMISSION_TYPE_MULTIPLE_DOWNLOADS as u32,
+        Permission::MultipleDownloads,
+    ),
+    (
+        CEF_PERMISSION_TYPE_LOCAL_FONTS as u32,
+        Permission::LocalFonts,
+    ),
+    (
+        CEF_PERMISSION_TYPE_POINTER_LOCK as u32,
+        Permission::PointerLock,
+    ),
+    (
+        CEF_PERMISSION_TYPE_KEYBOARD_LOCK as u32,
+        Permission::KeyboardLock,
+    ),
+    (
+        CEF_PERMISSION_TYPE_IDLE_DETECTION as u32,
+        Permission::IdleDetection,
+    ),
+    (
+        CEF_PERMISSION_TYPE_WINDOW_MANAGEMENT as u32,
+        Permission::WindowManagement,
+    ),
+    (
+        CEF_PERMISSION_TYPE_STORAGE_ACCESS as u32,
+        Permission::StorageAccess,
+    ),
+    (
+        CEF_PERMISSION_TYPE_TOP_LEVEL_STORAGE_ACCESS as u32,
+        Permission::StorageAccess,
+    ),
+    (
+        CEF_PERMISSION_TYPE_PROTECTED_MEDIA_IDENTIFIER as u32,
+        Permission::ProtectedMedia,
+    ),
+    (
+        CEF_PERMISSION_TYPE_LOCAL_NETWORK_ACCESS as u32,
+        Permission::LocalNetworkAccess,
+    ),
+];
+
+/// Media access bits, desktop capture is shown as screen capture
+const MEDIA_PERMISSIONS: &[(u32, Permission)] = &[
+    (
+        CEF_MEDIA_PERMISSION_DEVICE_AUDIO_CAPTURE as u32,
+        Permission::Microphone,
+    ),
+    (
+        CEF_MEDIA_PERMISSION_DEVICE_VIDEO_CAPTURE as u32,
+        Permission::Camera,
+    ),
+    (
+        CEF_MEDIA_PERMISSION_DESKTOP_AUDIO_CAPTURE as u32,
+        Permission::ScreenCapture,
+    ),
+    (
+        CEF_MEDIA_PERMISSION_DESKTOP_VIDEO_CAPTURE as u32,
+        Permission::ScreenCapture,
+    ),
+];
+
+/// Splits a permission bitmask, unknown bits become `Permission::Other`
+fn decode(bits: u32, known: &[(u32, Permission)]) -> Vec<Permission> {
+    let mut permissions = Vec::new();
+
+    for bit in (0..u32::BITS)
+        .map(|index| 1 << index)
+        .filter(|bit| bits & bit != 0)
+    {
+        let permission = known
+            .iter()
+            .find(|(known_bit, _)| *known_bit == bit)
+            .map(|(_, permission)| *permission)
+            .unwrap_or(Permission::Other);
+
+        if !permissions.contains(&permission) {
+            permissions.push(permission);
+        }
+    }
+
+    permissions
+}
+
+/// Forwards a request to the user, denying it right away when there is no origin to ask for
+fn send_request(
+    sender: &Sender<ChromiumEvent>,
+    origin: Option<&CefString>,
+    permissions: Vec<Permission>,
+    callback: PermissionCallback,
+) {
+    let origin = origin.map(|origin| origin.to_string()).unwrap_or_default();
+    let request = PermissionRequest::new(origin, permissions, callback);
+
+    if request.origin.is_empty() {
+        request.deny();
+        return;
+    }
+
+    sender.send(ChromiumEvent::PermissionRequest(request)).ok();
+}
 
 wrap_permission_handler! {
-    pub struct ChromiumPermissionHandler;
+    pub struct ChromiumPermissionHandler {
+        sender: Sender<ChromiumEvent>,
+    }
 
     impl PermissionHandler {
+        fn on_request_media_access_permission(
+            &self,
+            _browser: Option<&mut Browser>,
+            _frame: Option<&mut Frame>,
+            requesting_origin: Option<&CefString>,
+            requested_permissions: u32,
+            callback: Option<&mut MediaAccessCallback>,
+        ) -> i32 {
+            let Some(callback) = callback else {
+                return false.into();
+            };
+
+            let permissions = decode(requested_permissions, MEDIA_PERMISSIONS);
+            let callback = PermissionCallback::Media(callback.clone(), requested_permissions);
+
+            send_request(&self.sender, requesting_origin, permissions, callback);
+
+            true.into()
+        }
+
         fn on_show_permission_prompt(
             &self,
             _browser: Option<&mut Browser>,
             _prompt_id: u64,
-            _requesting_origin: Option<&CefString>,
+            requesting_origin: Option<&CefString>,
             requested_permissions: u32,
             callback: Option<&mut PermissionPromptCallback>,
         ) -> i32 {
-            println!("{}", requested_permissions);
-            if requested_permissions == CEF_PERMISSION_TYPE_LOCAL_NETWORK_ACCESS as u32
-                && let Some(callback) = callback {
-                    callback.cont(CEF_PERMISSION_RESULT_ACCEPT.into());
-                    return true.into();
-                }
-
-            Default::default()
+            let Some(callback) = callback else {
+                return false.into();
+            };
+
+            let permissions = decode(requested_permissions, PROMPT_PERMISSIONS);
+            let callback = PermissionCallback::Prompt(callback.clone());
+
+            send_request(&self.sender, requesting_origin, permissions, callback);
+
+            true.into()
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decoded(bits: u32, known: &[(u32, Permission)]) -> Vec<Permission> {
+        let mut permissions = decode(bits, known);
+        permissions.sort();
+        permissions
+    }
+
+    #[test]
+    fn prompt_permissions() {
+        assert!(decode(0, PROMPT_PERMISSIONS).is_empty());
+
+        let bits = CEF_PERMISSION_TYPE_CAMERA_STREAM as u32 | CEF_PERMISSION_TYPE_MIC_STREAM as u32;
+        assert_eq!(
+            decoded(bits, PROMPT_PERMISSIONS),
+            [Permission::Camera, Permission::Microphone]
+        );
+
+        let bits = CEF_PERMISSION_TYPE_STORAGE_ACCESS as u32
+            | CEF_PERMISSION_TYPE_TOP_LEVEL_STORAGE_ACCESS as u32;
+        assert_eq!(
+            decoded(bits, PROMPT_PERMISSIONS),
+            [Permission::StorageAccess]
+        );
+
+        let bits = CEF_PERMISSION_TYPE_LOCAL_NETWORK_ACCESS as u32;
+        assert_eq!(
+            decoded(bits, PROMPT_PERMISSIONS),
+            [Permission::LocalNetworkAccess]
+        );
+    }
+
+    #[test]
+    fn unknown_permissions() {
+        let known = [(1 << 0, Permission::Camera)];
+
+        assert_eq!(decoded(1 << 31, &known), [Permission::Other]);
+        assert_eq!(decoded(1 << 30 | 1 << 31, &known), [Permission::Other]);
+        assert_eq!(
+            decoded(1 << 0 | 1 << 31, &known),
+            [Permission::Camera, Permission::Other]
+        );
+    }
+
+    #[test]
+    fn media_permissions() {
+        let bits = CEF_MEDIA_PERMISSION_DEVICE_AUDIO_CAPTURE as u32
+            | CEF_MEDIA_PERMISSION_DEVICE_VIDEO_CAPTURE as u32;
+        assert_eq!(
+            decoded(bits, MEDIA_PERMISSIONS),
+            [Permission::Camera, Permission::Microphone]
+        );
+
+        let bits = CEF_MEDIA_PERMISSION_DESKTOP_AUDIO_CAPTURE as u32
+            | CEF_MEDIA_PERMISSION_DESKTOP_VIDEO_CAPTURE as u32;
+        assert_eq!(
+            decoded(bits, MEDIA_PERMISSIONS),
+            [Permission::ScreenCapture]
+        );
+    }
+}
@@ -186,5 +186,30 @@ wrap_render_handler! {
                 }
             }
         }
+
+        fn on_text_selection_changed(
+            &self,
+            _browser: Option<&mut Browser>,
+            selected_text: Option<&CefString>,
+            _selected_range: Option<&Range>,
+        ) {
+            let text = selected_text.map(|text| text.to_string()).unwrap_or_default();
+            self.sender.send(ChromiumEvent::TextSelection(text)).ok();
+        }
+
+        fn on_ime_composition_range_changed(
+            &self,
+            _browser: Option<&mut Browser>,
+            _selected_range: Option<&Range>,
+            character_bounds: Option<&[Rect]>,
+        ) {
+            // Anchor the input method candidate window to the end of the composition
+            if let Some(bounds) = character_bounds.and_then(|bounds| bounds.last()) {
+                // Unlike frames, a lost position would misplace the window until the next one,
+                // the channel is unbounded so this only fails once the app is gone
+                let event = ChromiumEvent::ImeBounds(bounds.x, bounds.y, bounds.width, bounds.height);
+                self.sender.send(event).ok();
+            }
+        }
     }
 }
@@ -0,0 +1,166 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use cef::sys::cef_errorcode_t::{self, *};
+use cef::{rc::*, *};
+use flume::Sender;
+use sha2::{Digest, Sha256};
+use tracing::warn;
+use url::{Host, Url};
+
+use crate::chromium::{
+    ChromiumEvent,
+    config::{MAX_PROXY_AUTH_ATTEMPTS, STREAMING_SERVER_DOMAIN},
+    types::{CertificateError, CertificateProblem},
+};
+
+/// Username and password of the configured proxy, given when it asks for them
+static PROXY_CREDENTIALS: OnceLock<(String, String)> = OnceLock::new();
+
+pub fn set_proxy_credentials(username: String, password: String) {
+    PROXY_CREDENTIALS.set((username, password)).ok();
+}
+
+wrap_request_handler! {
+    pub struct ChromiumRequestHandler {
+        sender: Sender<ChromiumEvent>,
+        /// Times the proxy asked for credentials, by host and realm
+        proxy_attempts: Arc<Mutex<HashMap<String, usize>>>,
+    }
+
+    impl RequestHandler {
+        fn on_certificate_error(
+            &self,
+            _browser: Option<&mut Browser>,
+            cert_error: Errorcode,
+            request_url: Option<&CefString>,
+            ssl_info: Option<&mut Sslinfo>,
+            callback: Option<&mut Callback>,
+        ) -> i32 {
+            let (Some(request_url), Some(callback)) = (request_url, callback) else {
+                return false.into();
+            };
+
+            let url = request_url.to_string();
+            let Ok(parsed_url) = Url::parse(&url) else {
+                return false.into();
+            };
+
+            if is_trusted(&parsed_url) {
+                callback.cont();
+                return true.into();
+            }
+
+            let problem = match cef_errorcode_t::from(cert_error) {
+                ERR_CERT_COMMON_NAME_INVALID => CertificateProblem::CommonNameInvalid,
+                ERR_CERT_DATE_INVALID => CertificateProblem::DateInvalid,
+                ERR_CERT_AUTHORITY_INVALID => CertificateProblem::AuthorityInvalid,
+                ERR_CERT_REVOKED => CertificateProblem::Revoked,
+                _ => CertificateProblem::Invalid,
+            };
+
+            let certificate = ssl_info.and_then(|ssl_info| ssl_info.x509_certificate());
+            let subject = certificate
+                .as_ref()
+                .and_then(|certificate| certificate.subject())
+                .map(|subject| CefString::from(&subject.display_name()).to_string());
+            let issuer = certificate
+                .as_ref()
+                .and_then(|certificate| certificate.issuer())
+                .map(|issuer| CefString::from(&issuer.display_name()).to_string());
+            let fingerprint = certificate.as_ref().and_then(fingerprint);
+
+            let host = parsed_url.host_str().unwrap_or_default().to_owned();
+            let error = CertificateError::new(
+                host,
+                url,
+                problem,
+                subject,
+                issuer,
+                fingerprint,
+                callback.clone(),
+            );
+            self.sender.send(ChromiumEvent::CertificateError(error)).ok();
+
+            // The request waits for the user through the callback
+            true.into()
+        }
+
+        fn auth_credentials(
+            &self,
+            _browser: Option<&mut Browser>,
+            _origin_url: Option<&CefString>,
+            is_proxy: i32,
+            host: Option<&CefString>,
+            port: i32,
+            realm: Option<&CefString>,
+            _scheme: Option<&CefString>,
+            callback: Option<&mut AuthCallback>,
+        ) -> i32 {
+            // Only the proxy from the settings, sites asking for credentials are cancelled
+            let (1, Some((username, password)), Some(callback)) =
+                (is_proxy, PROXY_CREDENTIALS.get(), callback)
+            else {
+                return false.into();
+            };
+
+            let host = format!("{}:{port}", host.map(CefString::to_string).unwrap_or_default());
+            let realm = realm.map(CefString::to_string).unwrap_or_default();
+
+            let Ok(mut proxy_attempts) = self.proxy_attempts.lock() else {
+                return false.into();
+            };
+
+            // Asking again means the credentials were refused, they stay the same until a restart
+            let attempts = proxy_attempts.entry(format!("{host} {realm}")).or_default();
+            *attempts += 1;
+            if *attempts > MAX_PROXY_AUTH_ATTEMPTS {
+                if *attempts == MAX_PROXY_AUTH_ATTEMPTS + 1 {
+                    warn!("Proxy {host} refused the credentials from the settings");
+                    self.sender.send(ChromiumEvent::ProxyAuthFailed(host)).ok();
+                }
+
+                return false.into();
+            }
+
+            callback.cont(
+                Some(&CefString::from(username.as_str())),
+                Some(&CefString::from(password.as_str())),
+            );
+
+            true.into()
+        }
+    }
+}
+
+/// SHA-256 of the DER encoded certificate, like browsers show it
+fn fingerprint(certificate: &X509Certificate) -> Option<String> {
+    let der = certificate.der_encoded()?;
+
+    let mut data = vec![0u8; der.size()];
+    let size = der.data(data.as_mut_ptr().cast(), data.len(), 0);
+    data.truncate(size);
+
+    if data.is_empty() {
+        return None;
+    }
+
+    let digest = Sha256::digest(&data);
+    Some(digest.iter().map(|byte| format!("{byte:02X}")).collect())
+}
+
+/// This machine and the HTTPS endpoint of the local streaming server
+fn is_trusted(url: &Url) -> bool {
+    match url.host() {
+        Some(Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(Host::Ipv6(ip)) => ip.is_loopback(),
+        Some(Host::Domain(domain)) => {
+            domain == "localhost"
+                || domain.ends_with(".localhost")
+                || domain.ends_with(&format!(".{STREAMING_SERVER_DOMAIN}"))
+        }
+        None => false,
+    }
+}
@@ -2,19 +2,32 @@ mod browser_process_handler;
 mod client;
 mod process;
 mod render_process_handler;
+mod scheme_handler;
 
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex, RwLock};
 
+use cef::sys::cef_scheme_options_t::{
+    CEF_SCHEME_OPTION_CORS_ENABLED, CEF_SCHEME_OPTION_FETCH_ENABLED, CEF_SCHEME_OPTION_SECURE,
+    CEF_SCHEME_OPTION_STANDARD,
+};
+
 use cef::{rc::*, *};
 use flume::Sender;
 
 use crate::chromium::{
-    ChromiumEvent, app::render_process_handler::ChromiumRenderProcessHandler, config::CMD_SWITCHES,
+    ChromiumEvent, app::render_process_handler::ChromiumRenderProcessHandler, switches::Switches,
     types::Viewport,
 };
-use crate::shared::pbo_manager::{BufferPool, PboManager};
+use crate::config::WEB_UI_SCHEME;
+use crate::shared::{
+    pbo_manager::{BufferPool, PboManager},
+    proxy::Proxy,
+};
 use browser_process_handler::ChromiumBrowserProcessHandler;
 
+pub use client::{ChromiumClient, browser_settings, window_info};
+
 wrap_app! {
     pub struct ChromiumApp {
         browser: Arc<Mutex<Option<Browser>>>,
@@ -22,54 +35,31 @@ wrap_app! {
         sender: Sender<ChromiumEvent>,
         pbo_manager: Arc<PboManager>,
         buffer_pool: Arc<BufferPool>,
+        switches: Arc<Switches>,
+        proxy: Arc<RwLock<Proxy>>,
+        web_ui: Option<PathBuf>,
     }
 
     impl App {
+        fn on_register_custom_schemes(&self, registrar: Option<&mut SchemeRegistrar>) {
+            // Registered in every process, served like https so the web UI keeps working unchanged
+            if let Some(registrar) = registrar {
+                let options = CEF_SCHEME_OPTION_STANDARD as i32
+                    | CEF_SCHEME_OPTION_SECURE as i32
+                    | CEF_SCHEME_OPTION_CORS_ENABLED as i32
+                    | CEF_SCHEME_OPTION_FETCH_ENABLED as i32;
+
+                registrar.add_custom_scheme(Some(&CefString::from(WEB_UI_SCHEME)), options);
+            }
+        }
+
         fn on_before_command_line_processing(
             &self,
             _process_type: Option<&CefString>,
             command_line: Option<&mut CommandLine>,
         ) {
             if let Some(line) = command_line {
-                CMD_SWITCHES.iter().for_each(|switch| {
-                    if let Some((key, value)) = switch.split_once('=') {
-                        line.append_switch_with_value(
-                            Some(&CefString::from(key)),
-                            Some(&CefString::from(value)),
-                        );
-                    } else {
-                        line.append_switch(Some(&CefString::from(switch.to_owned())));
-                    }
-                });
-
-                // Manual overrides if needed (currently handling everything via config.rs)
-                line.append_switch(Some(&CefString::from("disable-site-isolation-trials")));
-                line.append_switch(Some(&CefString::from("disable-extensions")));
-                line.append_switch(Some(&CefString::from("no-zygote")));
-                line.append_switch(Some(&CefString::from("no-proxy-server")));
-                line.append_switch(Some(&CefString::from("ignore-certificate-errors")));
-                line.append_switch(Some(&CefString::from("disable-web-security")));
-                line.append_switch(Some(&CefString::from("allow-running-insecure-content")));
-
-                // High-Performance GPU Flags
-                line.append_switch(Some(&CefString::from("enable-gpu-rasterization")));
-                line.append_switch(Some(&CefString::from("enable-zero-copy")));
-                line.append_switch(Some(&CefString::from("ignore-gpu-blocklist")));
-                line.append_switch(Some(&CefString::from("disable-gpu-driver-bug-workarounds")));
-
-                // Reduce compositor stalls
-                // Removed disable-threaded-animation to allow off-thread animations
-                // Removed disable-checker-imaging
-                // Removed disable-image-animation-resync
-                line.append_switch(Some(&CefString::from("disable-renderer-backgrounding")));
-
-                // Improve JavaScript scheduling
-                line.append_switch(Some(&CefString::from("disable-background-timer-throttling")));
-                line.append_switch(Some(&CefString::from("disable-backgrounding-occluded-windows")));
-
-                // Disable features that can cause stalls
-                line.append_switch(Some(&CefString::from("disable-software-rasterizer")));
-                line.append_switch(Some(&CefString::from("disable-smooth-scrolling")));
+                self.switches.apply(line);
 
                 use crate::shared::types::SCALE_FACTOR;
                 let scale_factor = SCALE_FACTOR.load(std::sync::atomic::Ordering::Relaxed);
@@ -90,6 +80,8 @@ wrap_app! {
                 self.sender.clone(),
                 self.pbo_manager.clone(),
                 self.buffer_pool.clone(),
+                self.proxy.clone(),
+                self.web_ui.clone(),
             ))
         }
 
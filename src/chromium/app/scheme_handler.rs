@@ -0,0 +1,379 @@
+use std::{
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+    ptr,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+};
+
+use cef::{rc::*, *};
+use flume::Sender;
+use percent_encoding::percent_decode_str;
+use tracing::{debug, warn};
+use url::Url;
+use zip::ZipArchive;
+
+/// Content types of the files found in a web UI bundle
+const MIME_TYPES: &[(&str, &str)] = &[
+    ("html", "text/html"),
+    ("js", "text/javascript"),
+    ("mjs", "text/javascript"),
+    ("css", "text/css"),
+    ("json", "application/json"),
+    ("map", "application/json"),
+    ("webmanifest", "application/manifest+json"),
+    ("wasm", "application/wasm"),
+    ("svg", "image/svg+xml"),
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("ico", "image/x-icon"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("ttf", "font/ttf"),
+    ("otf", "font/otf"),
+    ("txt", "text/plain"),
+];
+
+const DEFAULT_MIME_TYPE: &str = "application/octet-stream";
+const INDEX_FILE: &str = "index.html";
+
+/// A web UI bundle, either a directory or a zip archive with `index.html` at its root
+pub enum Bundle {
+    Directory(PathBuf),
+    Archive(Mutex<ZipArchive<File>>),
+}
+
+impl Bundle {
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        if path.is_dir() {
+            return Ok(Self::Directory(path.canonicalize()?));
+        }
+
+        let archive = ZipArchive::new(File::open(path)?)?;
+        Ok(Self::Archive(Mutex::new(archive)))
+    }
+
+    /// Reads a file by its path relative to the bundle, with the name it was found under
+    fn read(&self, path: &str) -> Option<(Vec<u8>, String)> {
+        match self {
+            Self::Directory(root) => {
+                let mut file = root.join(path);
+                if file.is_dir() {
+                    file.push(INDEX_FILE);
+                }
+
+                // Symlinks may still point out of the bundle
+                let file = file.canonicalize().ok()?;
+                if !file.starts_with(root) {
+                    warn!("Blocked a web UI request outside of the bundle: {path}");
+                    return None;
+                }
+
+                let data = fs::read(&file).ok()?;
+                Some((data, file.to_string_lossy().into_owned()))
+            }
+            Self::Archive(archive) => {
+                let mut archive = archive.lock().ok()?;
+
+                // Directories have no entry of their own to read, only their index
+                let index = match path {
+                    "" => INDEX_FILE.to_owned(),
+                    path => format!("{path}/{INDEX_FILE}"),
+                };
+
+                [path, index.as_str()].into_iter().find_map(|name| {
+                    let mut file = archive.by_name(name).ok()?;
+                    if file.is_dir() {
+                        return None;
+                    }
+
+                    let mut data = Vec::new();
+                    file.read_to_end(&mut data).ok()?;
+                    Some((data, name.to_owned()))
+                })
+            }
+        }
+    }
+}
+
+/// A file of the bundle, read at once as bundles are made of small files
+#[derive(Default)]
+struct Resource {
+    status: i32,
+    mime_type: &'static str,
+    data: Vec<u8>,
+    offset: usize,
+}
+
+impl Resource {
+    fn load(bundle: &Bundle, url: &str) -> Self {
+        match resolve(url).and_then(|path| bundle.read(&path)) {
+            Some((data, name)) => Self {
+                status: 200,
+                mime_type: mime_type(Path::new(&name)),
+                data,
+                offset: 0,
+            },
+            None => {
+                debug!("Not found in the web UI bundle: {url}");
+
+                Self {
+                    status: 404,
+                    mime_type: "text/plain",
+                    data: Vec::new(),
+                    offset: 0,
+                }
+            }
+        }
+    }
+}
+
+/// A request waiting for its file
+struct Load {
+    url: String,
+    resource: Arc<Mutex<Resource>>,
+    cancelled: Arc<AtomicBool>,
+    callback: Callback,
+}
+
+/// Reads the files of a bundle one after the other on a thread of its own, as reading may block
+pub struct Loader {
+    sender: Sender<Load>,
+}
+
+impl Loader {
+    /// The thread ends along with the loader
+    pub fn spawn(bundle: Bundle) -> Self {
+        let (sender, receiver) = flume::unbounded::<Load>();
+
+        thread::spawn(move || {
+            for load in receiver.iter() {
+                // Cancelled requests were dropped by the browser while waiting
+                if load.cancelled.load(Ordering::Acquire) {
+                    continue;
+                }
+
+                let loaded = Resource::load(&bundle, &load.url);
+                if let Ok(mut resource) = load.resource.lock() {
+                    *resource = loaded;
+                }
+
+                if !load.cancelled.load(Ordering::Acquire) {
+                    load.callback.cont();
+                }
+            }
+        });
+
+        Self { sender }
+    }
+}
+
+/// Maps an url to a path relative to the bundle, never outside of it
+fn resolve(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+
+    let mut segments = Vec::new();
+    for segment in url.path_segments()? {
+        // Encoded separators and dots would otherwise only show up once decoded
+        let segment = percent_decode_str(segment).decode_utf8().ok()?;
+
+        match segment.as_ref() {
+            "" | "." => {}
+            ".." => return None,
+            segment if segment.contains(['/', '\\', '\0']) => return None,
+            _ => segments.push(segment),
+        }
+    }
+
+    Some(segments.join("/"))
+}
+
+fn mime_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+
+    MIME_TYPES
+        .iter()
+        .find(|(it, _)| *it == extension)
+        .map(|(_, mime_type)| *mime_type)
+        .unwrap_or(DEFAULT_MIME_TYPE)
+}
+
+wrap_scheme_handler_factory! {
+    pub struct ChromiumSchemeHandlerFactory {
+        loader: Arc<Loader>,
+    }
+
+    impl SchemeHandlerFactory {
+        fn create(
+            &self,
+            _browser: Option<&mut Browser>,
+            _frame: Option<&mut Frame>,
+            _scheme_name: Option<&CefString>,
+            request: Option<&mut Request>,
+        ) -> Option<ResourceHandler> {
+            let url = CefString::from(&request?.url()).to_string();
+
+            Some(ChromiumResourceHandler::new(
+                self.loader.clone(),
+                url,
+                Arc::new(Mutex::new(Resource::default())),
+                Arc::new(AtomicBool::new(false)),
+            ))
+        }
+    }
+}
+
+wrap_resource_handler! {
+    struct ChromiumResourceHandler {
+        loader: Arc<Loader>,
+        url: String,
+        resource: Arc<Mutex<Resource>>,
+        cancelled: Arc<AtomicBool>,
+    }
+
+    impl ResourceHandler {
+        fn open(
+            &self,
+            _request: Option<&mut Request>,
+            handle_request: Option<&mut i32>,
+            callback: Option<&mut Callback>,
+        ) -> i32 {
+            let (Some(handle_request), Some(callback)) = (handle_request, callback) else {
+                return false.into();
+            };
+
+            // Reading may block, so the request continues once the file was read elsewhere
+            *handle_request = false.into();
+
+            let load = Load {
+                url: self.url.clone(),
+                resource: self.resource.clone(),
+                cancelled: self.cancelled.clone(),
+                callback: callback.clone(),
+            };
+
+            self.loader.sender.send(load).is_ok().into()
+        }
+
+        fn response_headers(
+            &self,
+            response: Option<&mut Response>,
+            response_length: Option<&mut i64>,
+            _redirect_url: Option<&mut CefString>,
+        ) {
+            let Ok(resource) = self.resource.lock() else {
+                return;
+            };
+
+            if let Some(response) = response {
+                response.set_status(resource.status);
+                response.set_mime_type(Some(&CefString::from(resource.mime_type)));
+            }
+
+            if let Some(response_length) = response_length {
+                *response_length = resource.data.len() as i64;
+            }
+        }
+
+        fn read(
+            &self,
+            data_out: *mut u8,
+            bytes_to_read: i32,
+            bytes_read: Option<&mut i32>,
+            _callback: Option<&mut ResourceReadCallback>,
+        ) -> i32 {
+            let (Ok(mut resource), Some(bytes_read)) = (self.resource.lock(), bytes_read) else {
+                return false.into();
+            };
+
+            let offset = resource.offset;
+            let count = (resource.data.len() - offset).min(bytes_to_read.max(0) as usize);
+
+            *bytes_read = count as i32;
+            if count == 0 {
+                return false.into();
+            }
+
+            // SAFETY: CEF guarantees `data_out` holds `bytes_to_read` bytes, `count` is at most
+            // that and never past the end of the data
+            unsafe {
+                ptr::copy_nonoverlapping(resource.data[offset..].as_ptr(), data_out, count);
+            }
+            resource.offset += count;
+
+            true.into()
+        }
+
+        fn cancel(&self) {
+            self.cancelled.store(true, Ordering::Release);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths() {
+        let cases = [
+            ("stremio-app://web/", Some("")),
+            (
+                "stremio-app://web/index.html?query#hash",
+                Some("index.html"),
+            ),
+            (
+                "stremio-app://web/scripts//main.js",
+                Some("scripts/main.js"),
+            ),
+            (
+                "stremio-app://web/fonts/Plus%20Jakarta.woff2",
+                Some("fonts/Plus Jakarta.woff2"),
+            ),
+            (
+                "stremio-app://web/scripts/../index.html",
+                Some("index.html"),
+            ),
+            // Encoded dots are folded by the url parser too, which never goes above the root
+            (
+                "stremio-app://web/%2e%2e/settings.json",
+                Some("settings.json"),
+            ),
+            (
+                "stremio-app://web/%2E%2E/%2E%2E/etc/passwd",
+                Some("etc/passwd"),
+            ),
+        ];
+
+        for (url, path) in cases {
+            assert_eq!(resolve(url).as_deref(), path, "{url}");
+        }
+    }
+
+    #[test]
+    fn traversal() {
+        let urls = [
+            "stremio-app://web/scripts%2F..%2F..%2Fsettings.json",
+            "stremio-app://web/..%2Fsettings.json",
+            "stremio-app://web/..%5Csettings.json",
+            "stremio-app://web/%2Fetc%2Fpasswd",
+            "stremio-app://web/index.html%00.js",
+            "stremio-app://web/%ff",
+        ];
+
+        for url in urls {
+            assert_eq!(resolve(url), None, "{url}");
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, RwLock};
+
+use cef::*;
+use flume::Receiver;
+use url::Url;
+
+use crate::chromium::{
+    ChromiumEvent,
+    app::{ChromiumClient, browser_settings, window_info},
+    types::Viewport,
+    view::BrowserView,
+};
+use crate::shared::pbo_manager::{BufferPool, PboManager};
+
+/// A browser opened next to the main one for pages the web UI hands off, like logins,
+/// with its own events and frames so it can be shown in a window of its own
+pub struct AuxiliaryBrowser {
+    view: BrowserView,
+    receiver: Receiver<ChromiumEvent>,
+}
+
+impl AuxiliaryBrowser {
+    pub(super) fn new(url: &Url, viewport: Viewport) -> Option<Self> {
+        let browser = Arc::new(Mutex::new(None));
+        let viewport = Arc::new(RwLock::new(viewport));
+        // Frames only carry their dirty regions, buffers can't be shared with the main browser
+        let pbo_manager = Arc::new(PboManager::default());
+        let buffer_pool = Arc::new(BufferPool::default());
+
+        let (sender, receiver) = flume::unbounded();
+        let mut client = ChromiumClient::new(
+            browser.clone(),
+            viewport.clone(),
+            sender,
+            pbo_manager,
+            buffer_pool,
+            // Logins happen on third-party pages, which must not see the shell's IPC
+            false,
+            Default::default(),
+            Default::default(),
+        );
+
+        // Not on the CEF UI thread, the browser is stored once it was created
+        let url = CefString::from(url.as_str());
+        let created = browser_host_create_browser(
+            Some(&window_info()),
+            Some(&mut client),
+            Some(&url),
+            Some(&browser_settings()),
+            None,
+            None,
+        );
+
+        if created != 1 {
+            tracing::error!("Failed to create an auxiliary browser");
+            return None;
+        }
+
+        Some(Self {
+            view: BrowserView::new(browser, viewport),
+            receiver,
+        })
+    }
+
+    pub fn on_event<F: Fn(ChromiumEvent)>(&self, handler: F) {
+        self.receiver.try_iter().for_each(handler);
+    }
+}
+
+impl Deref for AuxiliaryBrowser {
+    type Target = BrowserView;
+
+    fn deref(&self) -> &Self::Target {
+        &self.view
+    }
+}
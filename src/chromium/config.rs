@@ -1,5 +1,31 @@
 pub const MAX_FRAME_RATE: f64 = 1000.0;
 
+/// Zoom factor of a single zoom level step, as used by Chromium
+pub const ZOOM_LEVEL_FACTOR: f64 = 1.2;
+pub const MIN_ZOOM_LEVEL: f64 = -5.0;
+pub const MAX_ZOOM_LEVEL: f64 = 9.0;
+
+/// Domain of the certificates served by the local streaming server on its HTTPS port
+pub const STREAMING_SERVER_DOMAIN: &str = "519b6502d940.stremio.rocks";
+
+/// Scheme of the deeplinks handled by the web UI
+pub const INTERNAL_SCHEME: &str = "stremio";
+
+/// Schemes of the popups opened in the system
+pub const EXTERNAL_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Login pages opened in an in-app window, so they can report back to the web UI
+pub const AUXILIARY_ORIGINS: &[&str] = &[
+    "https://trakt.tv",
+    "https://api.trakt.tv",
+    "https://www.facebook.com",
+    "https://m.facebook.com",
+    "https://accounts.google.com",
+];
+
+/// Tries of the proxy credentials, a refused first try is retried once
+pub const MAX_PROXY_AUTH_ATTEMPTS: usize = 2;
+
 pub const IPC_SENDER: &str = "__postMessage";
 pub const IPC_RECEIVER: &str = "__onMessage";
 
@@ -7,13 +33,12 @@ pub const IPC_MESSAGE: &str = "IPC";
 pub const READY_MESSAGE: &str = "READY";
 
 pub const IPC_SCRIPT: &str = include_str!("ipc.js");
+/// Inserts text at a point of the page, only when an editable element is under it
+pub const PRIMARY_PASTE_SCRIPT: &str = include_str!("paste.js");
 
 pub const CMD_SWITCHES: &[&str] = &[
     "use-angle=gl-egl",
-    "enable-gpu",
-    "ignore-gpu-blocklist",
     "js-flags=--max-old-space-size=4096 --expose-gc --no-optimize-for-size",
-    "enable-oop-rasterization",
     "process-per-site",
     "disable-quic",
     "disable-background-networking",
@@ -24,4 +49,27 @@ pub const CMD_SWITCHES: &[&str] = &[
     "disable-features=BackForwardCache",
     "renderer-process-limit=2",
     "max-active-webgl-contexts=1",
+    "disable-site-isolation-trials",
+    "disable-extensions",
+    "no-zygote",
+    // Reduce compositor stalls
+    "disable-renderer-backgrounding",
+    // Improve JavaScript scheduling
+    "disable-background-timer-throttling",
+    "disable-backgrounding-occluded-windows",
+    "disable-smooth-scrolling",
+];
+
+/// Switches relaxing web security, left out in safe mode
+pub const INSECURE_SWITCHES: &[&str] = &["disable-web-security", "allow-running-insecure-content"];
+
+/// High performance GPU switches, left out in safe mode
+pub const GPU_SWITCHES: &[&str] = &[
+    "enable-gpu",
+    "ignore-gpu-blocklist",
+    "enable-oop-rasterization",
+    "enable-gpu-rasterization",
+    "enable-zero-copy",
+    "disable-gpu-driver-bug-workarounds",
+    "disable-software-rasterizer",
 ];
@@ -57,6 +57,22 @@ window.ipc.addEventListener('message', (message) => {
     window.qt.webChannelTransport.onmessage(message);
 });
 
+// Shell events that are not handled by the web app
+window.ipc.addEventListener('message', async (message) => {
+    try {
+        const { args } = JSON.parse(message.data);
+        if (!Array.isArray(args) || args[0] !== 'next-video')
+            return;
+
+        const state = await window.services.core.transport.getState('player');
+        const deepLink = state && state.nextVideo && state.nextVideo.deepLinks && state.nextVideo.deepLinks.player;
+        if (deepLink)
+            window.location.hash = deepLink;
+    } catch (err) {
+        console.error('Failed to play the next video', err);
+    }
+});
+
 console.log('IPC script injected');
 
 // Metadata Scraper Logic
@@ -64,9 +80,11 @@ console.log('IPC script injected');
     let lastTitle = "";
     let lastPoster = "";
     let lastLogo = "";
+    let lastId = "";
+    let lastNextVideo = false;
 
     let servicesHooked = false;
-    let internalMetadata = { title: "", artist: "", poster: "", logo: "" };
+    let internalMetadata = { title: "", artist: "", poster: "", logo: "", id: "", type: "", genres: null, nextVideo: false };
 
     function hookServices() {
         if (!servicesHooked && window.services && window.services.core) {
@@ -77,8 +95,9 @@ console.log('IPC script injected');
                         if (window.services && window.services.core && window.services.core.transport) {
                             const state = await window.services.core.transport.getState('player');
                             if (state && state.event && state.event.name === 'video-changed') {
-                                internalMetadata = { title: "", artist: "", poster: "", logo: "" };
+                                internalMetadata = { title: "", artist: "", poster: "", logo: "", id: "", type: "", genres: null, nextVideo: false };
                             }
+                            internalMetadata.nextVideo = !!(state && state.nextVideo);
                             if (state && state.metaItem) {
                                 let seriesName = state.metaItem.name || "";
                                 let epTitle = "";
@@ -123,6 +142,14 @@ console.log('IPC script injected');
                                     internalMetadata.logo = state.metaItem.logo;
                                 }
 
+                                // Sent as they are, the shell decides what counts as adult content
+                                internalMetadata.genres = (state.metaItem.links || [])
+                                    .filter(link => link.category === "Genres")
+                                    .map(link => link.name)
+                                    .concat(state.metaItem.genres || []);
+
+                                internalMetadata.id = state.metaItem.id || "";
+                                internalMetadata.type = state.metaItem.type || "";
                                 internalMetadata.title = epTitle;
                                 internalMetadata.artist = seriesName;
                                 internalMetadata.poster = art; // Map 'art' to 'poster' for IPC
@@ -195,10 +222,12 @@ console.log('IPC script injected');
             }
         }
 
-        if (title !== lastTitle || poster !== lastPoster || logo !== lastLogo) {
+        if (title !== lastTitle || poster !== lastPoster || logo !== lastLogo || internalMetadata.id !== lastId || internalMetadata.nextVideo !== lastNextVideo) {
             lastTitle = title;
             lastPoster = poster;
             lastLogo = logo;
+            lastId = internalMetadata.id;
+            lastNextVideo = internalMetadata.nextVideo;
 
             // Send to Rust
             // Note: We map 'poster' (which holds the artwork/thumbnail URL) to 'poster' field.
@@ -219,7 +248,11 @@ console.log('IPC script injected');
                     artist: artist,
                     poster: poster,
                     thumbnail: poster, // Send same URL as thumbnail for MPRIS preference
-                    logo: logo
+                    logo: logo,
+                    id: internalMetadata.id || null,
+                    type: internalMetadata.type || null,
+                    genres: internalMetadata.genres,
+                    nextVideo: internalMetadata.nextVideo
                 }]
             }));
         }
@@ -1,40 +1,66 @@
 mod app;
+mod auxiliary;
 mod config;
+mod popup;
+mod switches;
 mod types;
+mod view;
 
-use std::path::{Path, PathBuf};
+use std::ops::Deref;
+use std::path::Path;
 use std::ptr;
 use std::sync::{Arc, Mutex, RwLock};
 
-use cef::sys::{
-    cef_drag_operations_mask_t as OperationsMask, cef_key_event_type_t::KEYEVENT_CHAR,
-    cef_log_severity_t::LOGSEVERITY_VERBOSE, cef_mouse_button_type_t::MBT_LEFT,
-    cef_mouse_button_type_t::MBT_MIDDLE, cef_mouse_button_type_t::MBT_RIGHT,
-    cef_paint_element_type_t::PET_VIEW,
-};
-use cef::{Frame as MainFrame, args::Args, *};
+use cef::sys::cef_log_severity_t::LOGSEVERITY_VERBOSE;
+use cef::{args::Args, *};
 use flume::Receiver;
 
 use app::ChromiumApp;
 use config::IPC_SENDER;
+use switches::Switches;
 use types::Viewport;
+
+pub use auxiliary::AuxiliaryBrowser;
+pub use types::{
+    CertificateError, CertificateProblem, ConsoleMessage, ContextMenu, Download, DownloadRequest,
+    DownloadState, FileDialog, FileDialogMode, PermissionRequest,
+};
 use url::Url;
+pub use view::BrowserView;
 
-use crate::chromium::config::MAX_FRAME_RATE;
+use crate::settings::Settings as AppSettings;
 use crate::shared::{
     Frame,
     pbo_manager::{BufferPool, PboManager},
-    states::{KeyboardState, PointerState},
+    proxy::Proxy,
+    types::BrowserCursor,
 };
 
 #[derive(Debug)]
 pub enum ChromiumEvent {
     Ready,
     Loaded,
+    Closed,
     Fullscreen(bool),
     Render(Frame),
     Open(Url),
+    OpenAuxiliary(Url),
+    Deeplink(String),
     Ipc(String),
+    TextSelection(String),
+    ContextMenu(ContextMenu),
+    Cursor(BrowserCursor),
+    Title(String),
+    Address(String),
+    Status(String),
+    Console(ConsoleMessage),
+    FileDialog(FileDialog),
+    CertificateError(CertificateError),
+    ProxyAuthFailed(String),
+    PermissionRequest(PermissionRequest),
+    DownloadRequest(DownloadRequest),
+    Download(Download),
+    ImeBounds(i32, i32, i32, i32),
 }
 
 pub struct Chromium {
@@ -42,23 +68,39 @@ pub struct Chromium {
     app: App,
     settings: Settings,
     browser: Arc<Mutex<Option<Browser>>>,
-    viewport: Arc<RwLock<Viewport>>,
+    view: BrowserView,
+    proxy: Arc<RwLock<Proxy>>,
     receiver: Receiver<ChromiumEvent>,
     pub pbo_manager: Arc<PboManager>,
     pub buffer_pool: Arc<BufferPool>,
 }
 
 impl Chromium {
-    pub fn new(data_dir: &Path) -> Self {
+    /// `flags` are extra Chromium switches given on the command line, see [`Switches`],
+    /// `web_ui` is a web UI bundle to serve on its own scheme
+    pub fn new(
+        data_dir: &Path,
+        app_settings: &AppSettings,
+        flags: &[String],
+        safe_mode: bool,
+        web_ui: Option<&Path>,
+    ) -> Self {
         let _ = api_hash(sys::CEF_API_VERSION_LAST, 0);
 
         let args = Args::new();
 
         let browser = Arc::new(Mutex::new(None));
-        let viewport = Arc::new(RwLock::new(Viewport::default()));
+        // Sized like the restored window so the first frame does not need a resize
+        let viewport = Arc::new(RwLock::new(Viewport::new(
+            app_settings.window.width,
+            app_settings.window.height,
+        )));
         let pbo_manager = Arc::new(PboManager::default());
         let buffer_pool = Arc::new(BufferPool::default());
 
+        let switches = Arc::new(Switches::new(&app_settings.chromium, flags, safe_mode));
+        let proxy = Arc::new(RwLock::new(Proxy::default()));
+
         let (sender, receiver) = flume::unbounded();
         let app = ChromiumApp::new(
             browser.clone(),
@@ -66,6 +108,9 @@ impl Chromium {
             sender,
             pbo_manager.clone(),
             buffer_pool.clone(),
+            switches,
+            proxy.clone(),
+            web_ui.map(Path::to_path_buf),
         );
 
         let cache_path = data_dir.join("cache");
@@ -86,8 +131,9 @@ impl Chromium {
             args,
             app,
             settings,
+            view: BrowserView::new(browser.clone(), viewport),
             browser,
-            viewport,
+            proxy,
             receiver,
             pbo_manager,
             buffer_pool,
@@ -108,6 +154,13 @@ impl Chromium {
         None
     }
 
+    /// Sets the proxy used by the browser, must be called before `start`
+    pub fn set_proxy(&self, proxy: Proxy) {
+        if let Ok(mut current) = self.proxy.write() {
+            *current = proxy;
+        }
+    }
+
     pub fn start(&mut self) {
         cef::initialize(
             Some(self.args.as_main_args()),
@@ -141,182 +194,28 @@ impl Chromium {
         }
     }
 
-    pub fn load_url(&self, url: &str) {
-        if let Some(main_frame) = self.main_frame() {
-            let url = CefString::from(url);
-            main_frame.load_url(Some(&url));
-        }
-    }
-
-    pub fn on_event<F: Fn(ChromiumEvent)>(&self, handler: F) {
-        self.receiver.try_iter().for_each(handler);
-    }
-
-    pub fn set_monitor_info(&self, refresh_rate: f64, scale_factor: i32) {
-        if let Ok(mut viewport) = self.viewport.write() {
-            viewport.scale_factor = scale_factor;
-        }
-
-        if let Some(browser_host) = self.browser_host() {
-            browser_host.set_windowless_frame_rate(refresh_rate.min(MAX_FRAME_RATE) as i32);
-            browser_host.notify_screen_info_changed();
-        }
-    }
-
-    pub fn resize(&self, width: i32, height: i32) {
-        if let Ok(mut viewport) = self.viewport.write() {
-            viewport.width = width;
-            viewport.height = height;
-
-            if let Some(browser_host) = self.browser_host() {
-                browser_host.was_resized();
-                browser_host.invalidate(PET_VIEW.into());
-                // Re-apply zoom level on resize as it might reset
-                browser_host.set_zoom_level(viewport.zoom_level);
-            }
-        }
-    }
-
-    pub fn set_zoom(&self, level: f64) {
-        if let Ok(mut viewport) = self.viewport.write() {
-            viewport.zoom_level = level;
-        }
-
-        if let Some(browser_host) = self.browser_host() {
-            browser_host.set_zoom_level(level);
-        }
-    }
-
-    pub fn hidden(&self, state: bool) {
-        if let Some(browser_host) = self.browser_host() {
-            browser_host.was_hidden(state.into());
-        }
-    }
-
-    pub fn focus(&self, state: bool) {
-        if let Some(browser_host) = self.browser_host() {
-            browser_host.set_focus(state.into());
-        }
-    }
-
-    pub fn forward_motion(&self, pointer_state: &PointerState) {
-        if let Some(browser_host) = self.browser_host() {
-            let mouse_event = MouseEvent::from(pointer_state);
-            let mouse_leave = (!pointer_state.over()).into();
-            browser_host.send_mouse_move_event(Some(&mouse_event), mouse_leave);
-        }
-    }
-
-    pub fn forward_scroll(&self, pointer_state: &PointerState, delta_x: f64, delta_y: f64) {
-        if let Some(browser_host) = self.browser_host() {
-            let mouse_event = MouseEvent::from(pointer_state);
-            browser_host.send_mouse_wheel_event(Some(&mouse_event), delta_x as i32, delta_y as i32);
-        }
-    }
-
-    pub fn forward_click(&self, pointer_state: &PointerState, count: i32) {
-        if let Some(browser_host) = self.browser_host() {
-            let pressed = pointer_state.pressed();
-            let button = pointer_state.button();
-
-            let mouse_event = MouseEvent::from(pointer_state);
-
-            let r#type = match button {
-                1 => Some(MBT_LEFT.into()),
-                2 => Some(MBT_MIDDLE.into()),
-                3 => Some(MBT_RIGHT.into()),
-                8 if self.go_back() => None,
-                9 if self.go_forward() => None,
-                _ => None,
-            };
-
-            let mouse_up = (!pressed).into();
-
-            if let Some(r#type) = r#type {
-                browser_host.send_mouse_click_event(Some(&mouse_event), r#type, mouse_up, count);
-            }
-        }
-    }
-
-    pub fn forward_key(&self, keyboard_state: &KeyboardState) {
-        if let Some(browser_host) = self.browser_host() {
-            if let Some(character) = keyboard_state.character()
-                && keyboard_state.pressed()
-                && !keyboard_state.control_modifier()
-            {
-                let event = cef::KeyEvent {
-                    type_: KEYEVENT_CHAR.into(),
-                    character: character as u16,
-                    ..Default::default()
-                };
-
-                browser_host.send_key_event(Some(&event));
-            }
-
-            let key_event = KeyEvent::from(keyboard_state);
-            browser_host.send_key_event(Some(&key_event));
-        }
-    }
-
-    pub fn forward_file_enter(&self, pointer_state: &PointerState, path: PathBuf) {
-        if let Some(browser_host) = self.browser_host() {
-            let file_path = path.to_str().map(CefString::from);
-            let file_name = path
-                .file_name()
-                .and_then(|name| name.to_str())
-                .map(CefString::from);
-
-            if let Some(mut drag_data) = cef::drag_data_create() {
-                drag_data.add_file(file_path.as_ref(), file_name.as_ref());
-
-                let mouse_event = MouseEvent::from(pointer_state);
-
-                browser_host.drag_target_drag_enter(
-                    Some(&mut drag_data),
-                    Some(&mouse_event),
-                    OperationsMask::DRAG_OPERATION_MOVE.into(),
-                );
-            }
-        }
-    }
-
-    pub fn forward_file_hover(&self, pointer_state: &PointerState) {
-        if let Some(browser_host) = self.browser_host() {
-            let mouse_event = MouseEvent::from(pointer_state);
-
-            browser_host.drag_target_drag_over(
-                Some(&mouse_event),
-                OperationsMask::DRAG_OPERATION_MOVE.into(),
+    pub fn inspect(&self, x: i32, y: i32) {
+        if let Some(host) = self.browser_host() {
+            let point = Point { x, y };
+            host.show_dev_tools(
+                None,
+                Option::<&mut Client>::None,
+                Option::<&BrowserSettings>::None,
+                Some(&point),
             );
         }
     }
 
-    pub fn forward_file_drop(&self, pointer_state: &PointerState) {
-        if let Some(browser_host) = self.browser_host() {
-            let mouse_event = MouseEvent::from(pointer_state);
+    /// Opens a page in a browser of its own, rendered apart from the main one
+    pub fn open_auxiliary(&self, url: &Url, width: i32, height: i32) -> Option<AuxiliaryBrowser> {
+        let mut viewport = Viewport::new(width, height);
+        viewport.scale_factor = self.scale_factor();
 
-            browser_host.drag_target_drop(Some(&mouse_event));
-        }
-    }
-
-    pub fn forward_file_leave(&self) {
-        if let Some(browser_host) = self.browser_host() {
-            browser_host.drag_target_drag_leave();
-        }
+        AuxiliaryBrowser::new(url, viewport)
     }
 
-    pub fn clipboard(&self, data: String) {
-        if let Some(browser_host) = self.browser_host() {
-            for character in data.chars() {
-                let event = cef::KeyEvent {
-                    type_: KEYEVENT_CHAR.into(),
-                    character: character as u16,
-                    ..Default::default()
-                };
-
-                browser_host.send_key_event(Some(&event));
-            }
-        }
+    pub fn on_event<F: Fn(ChromiumEvent)>(&self, handler: F) {
+        self.receiver.try_iter().for_each(handler);
     }
 
     pub fn post_message(&self, message: String) {
@@ -328,44 +227,12 @@ impl Chromium {
             main_frame.execute_java_script(Some(&code), None, 0);
         }
     }
+}
 
-    fn go_back(&self) -> bool {
-        if let Ok(browser) = self.browser.lock()
-            && let Some(browser) = browser.as_ref()
-        {
-            browser.go_back();
-        }
-
-        true
-    }
-
-    fn go_forward(&self) -> bool {
-        if let Ok(browser) = self.browser.lock()
-            && let Some(browser) = browser.as_ref()
-        {
-            browser.go_forward();
-        }
-
-        true
-    }
-
-    fn browser_host(&self) -> Option<BrowserHost> {
-        if let Ok(browser) = self.browser.lock()
-            && let Some(browser) = browser.as_ref()
-        {
-            return browser.host();
-        }
-
-        None
-    }
-
-    fn main_frame(&self) -> Option<MainFrame> {
-        if let Ok(browser) = self.browser.lock()
-            && let Some(browser) = browser.as_ref()
-        {
-            return browser.main_frame();
-        }
+impl Deref for Chromium {
+    type Target = BrowserView;
 
-        None
+    fn deref(&self) -> &Self::Target {
+        &self.view
     }
 }
@@ -0,0 +1,23 @@
+(x, y, text) => {
+    const target = document.elementFromPoint(x, y);
+    if (!target)
+        return;
+
+    const isTextField = (target instanceof HTMLInputElement || target instanceof HTMLTextAreaElement)
+        && !target.readOnly
+        && !target.disabled
+        && target.selectionStart !== null;
+
+    if (!isTextField && !target.isContentEditable)
+        return;
+
+    target.focus();
+
+    const caret = document.caretPositionFromPoint?.(x, y);
+    if (caret && isTextField && caret.offsetNode === target)
+        target.setSelectionRange(caret.offset, caret.offset);
+    else if (caret && !isTextField)
+        document.getSelection().collapse(caret.offsetNode, caret.offset);
+
+    document.execCommand('insertText', false, text);
+}
@@ -0,0 +1,103 @@
+use tracing::warn;
+use url::Url;
+
+use crate::chromium::config::{AUXILIARY_ORIGINS, EXTERNAL_SCHEMES, INTERNAL_SCHEME};
+
+/// Where a window opened by the page goes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PopupPolicy {
+    /// Opened in the system browser
+    External(Url),
+    /// Opened in an in-app window able to report back to the web UI
+    Auxiliary(Url),
+    /// Deeplink handled by the web UI itself
+    Internal(Url),
+    Blocked,
+}
+
+impl PopupPolicy {
+    pub fn for_url(url: &str) -> Self {
+        let Ok(url) = Url::parse(url) else {
+            warn!("Blocked popup with an invalid url: {url:?}");
+            return Self::Blocked;
+        };
+
+        let scheme = url.scheme();
+
+        if scheme == INTERNAL_SCHEME {
+            return Self::Internal(url);
+        }
+
+        if !EXTERNAL_SCHEMES.contains(&scheme) {
+            warn!("Blocked popup with an unsafe scheme: {url}");
+            return Self::Blocked;
+        }
+
+        let origin = url.origin().ascii_serialization();
+        if AUXILIARY_ORIGINS.contains(&origin.as_str()) {
+            return Self::Auxiliary(url);
+        }
+
+        Self::External(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policies() {
+        let url = |url: &str| Url::parse(url).unwrap();
+
+        let cases = [
+            (
+                "stremio:///detail/movie/tt0111161",
+                PopupPolicy::Internal(url("stremio:///detail/movie/tt0111161")),
+            ),
+            (
+                "https://www.stremio.com/",
+                PopupPolicy::External(url("https://www.stremio.com/")),
+            ),
+            (
+                "http://example.com/page",
+                PopupPolicy::External(url("http://example.com/page")),
+            ),
+            (
+                "mailto:support@stremio.com",
+                PopupPolicy::External(url("mailto:support@stremio.com")),
+            ),
+            (
+                "https://accounts.google.com/o/oauth2/auth",
+                PopupPolicy::Auxiliary(url("https://accounts.google.com/o/oauth2/auth")),
+            ),
+            (
+                "https://trakt.tv/oauth/authorize",
+                PopupPolicy::Auxiliary(url("https://trakt.tv/oauth/authorize")),
+            ),
+            // Same host on another scheme or port is another origin
+            (
+                "http://accounts.google.com/",
+                PopupPolicy::External(url("http://accounts.google.com/")),
+            ),
+            (
+                "https://accounts.google.com:8443/",
+                PopupPolicy::External(url("https://accounts.google.com:8443/")),
+            ),
+            (
+                "https://accounts.google.com.example.com/",
+                PopupPolicy::External(url("https://accounts.google.com.example.com/")),
+            ),
+            ("file:///etc/passwd", PopupPolicy::Blocked),
+            ("javascript:alert(1)", PopupPolicy::Blocked),
+            ("data:text/html,<h1>", PopupPolicy::Blocked),
+            ("ftp://example.com/", PopupPolicy::Blocked),
+            ("not a url", PopupPolicy::Blocked),
+            ("", PopupPolicy::Blocked),
+        ];
+
+        for (url, policy) in cases {
+            assert_eq!(PopupPolicy::for_url(url), policy, "{url}");
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use cef::{CefString, CommandLine, ImplCommandLine};
+use tracing::{info, warn};
+
+use crate::{
+    chromium::config::{CMD_SWITCHES, GPU_SWITCHES, INSECURE_SWITCHES},
+    settings::ChromiumSettings,
+};
+
+/// Chromium command-line switches, as `name` or `name=value`
+///
+/// Built from the defaults, then the settings file, then the command line.
+/// Later entries replace earlier ones with the same name,
+/// and `!name` removes a switch added before it.
+#[derive(Debug, Clone, Default)]
+pub struct Switches(Vec<(String, Option<String>)>);
+
+impl Switches {
+    pub fn new(settings: &ChromiumSettings, flags: &[String], safe_mode: bool) -> Self {
+        let safe_mode = safe_mode || settings.safe_mode;
+
+        let mut switches = Self::default();
+        switches.extend(CMD_SWITCHES.iter().copied());
+
+        if safe_mode {
+            info!("Starting in safe mode, insecure and GPU switches are disabled");
+        } else {
+            switches.extend(INSECURE_SWITCHES.iter().copied());
+            switches.extend(GPU_SWITCHES.iter().copied());
+        }
+
+        switches.extend(settings.switches.iter().map(String::as_str));
+        switches.extend(flags.iter().map(String::as_str));
+
+        switches
+    }
+
+    fn extend<'a>(&mut self, entries: impl IntoIterator<Item = &'a str>) {
+        for entry in entries {
+            let entry = entry.trim();
+
+            if let Some(name) = entry.strip_prefix('!') {
+                let name = name.trim_start_matches('-');
+                self.0.retain(|(it, _)| it != name);
+                continue;
+            }
+
+            let entry = entry.trim_start_matches('-');
+            let (name, value) = match entry.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_owned())),
+                None => (entry, None),
+            };
+
+            if name.is_empty() {
+                warn!("Ignoring invalid Chromium switch: {entry:?}");
+                continue;
+            }
+
+            match self.0.iter_mut().find(|(it, _)| it == name) {
+                Some((_, current)) => *current = value,
+                None => self.0.push((name.to_owned(), value)),
+            }
+        }
+    }
+
+    pub fn apply(&self, line: &mut CommandLine) {
+        for (name, value) in &self.0 {
+            let name = CefString::from(name.as_str());
+
+            match value {
+                Some(value) => {
+                    line.append_switch_with_value(
+                        Some(&name),
+                        Some(&CefString::from(value.as_str())),
+                    );
+                }
+                None => line.append_switch(Some(&name)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value<'a>(switches: &'a Switches, name: &str) -> Option<Option<&'a str>> {
+        switches
+            .0
+            .iter()
+            .find(|(it, _)| it == name)
+            .map(|(_, value)| value.as_deref())
+    }
+
+    #[test]
+    fn defaults() {
+        let switches = Switches::new(&ChromiumSettings::default(), &[], false);
+        assert_eq!(value(&switches, "disable-web-security"), Some(None));
+        assert_eq!(value(&switches, "enable-gpu"), Some(None));
+        assert_eq!(
+            value(&switches, "autoplay-policy"),
+            Some(Some("no-user-gesture-required"))
+        );
+    }
+
+    #[test]
+    fn safe_mode() {
+        let switches = Switches::new(&ChromiumSettings::default(), &[], true);
+        assert_eq!(value(&switches, "disable-web-security"), None);
+        assert_eq!(value(&switches, "enable-gpu"), None);
+        assert_eq!(value(&switches, "disable-extensions"), Some(None));
+
+        let settings = ChromiumSettings {
+            safe_mode: true,
+            ..Default::default()
+        };
+        let switches = Switches::new(&settings, &[], false);
+        assert_eq!(value(&switches, "disable-web-security"), None);
+    }
+
+    #[test]
+    fn overrides() {
+        let settings = ChromiumSettings {
+            switches: vec!["!enable-gpu".into(), "renderer-process-limit=4".into()],
+            ..Default::default()
+        };
+        let flags = [
+            "--renderer-process-limit=8".into(),
+            "!--disable-web-security".into(),
+            " lang=fr ".into(),
+            "=value".into(),
+        ];
+        let switches = Switches::new(&settings, &flags, false);
+
+        assert_eq!(value(&switches, "enable-gpu"), None);
+        assert_eq!(value(&switches, "disable-web-security"), None);
+        assert_eq!(value(&switches, "renderer-process-limit"), Some(Some("8")));
+        assert_eq!(value(&switches, "lang"), Some(Some("fr")));
+        assert_eq!(value(&switches, ""), None);
+        assert_eq!(
+            switches
+                .0
+                .iter()
+                .filter(|(it, _)| it == "renderer-process-limit")
+                .count(),
+            1
+        );
+    }
+}
@@ -1,3 +1,348 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use cef::sys::cef_permission_request_result_t::{
+    CEF_PERMISSION_RESULT_ACCEPT, CEF_PERMISSION_RESULT_DENY,
+};
+use cef::{
+    BeforeDownloadCallback, Callback, CefString, CefStringList, DownloadItemCallback,
+    FileDialogCallback, ImplBeforeDownloadCallback, ImplCallback, ImplDownloadItemCallback,
+    ImplFileDialogCallback, ImplMediaAccessCallback, ImplPermissionPromptCallback,
+    MediaAccessCallback, PermissionPromptCallback,
+};
+
+use crate::shared::types::Permission;
+
+/// What was under the pointer when the page asked for a context menu
+#[derive(Debug, Clone, Default)]
+pub struct ContextMenu {
+    pub x: i32,
+    pub y: i32,
+    pub link: Option<String>,
+    pub selection: String,
+    pub editable: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileDialogMode {
+    Open,
+    OpenMultiple,
+    OpenFolder,
+    Save,
+}
+
+/// A file chooser requested by the page, answered once with `select` or `cancel`
+pub struct FileDialog {
+    pub mode: FileDialogMode,
+    pub title: Option<String>,
+    pub default_path: Option<PathBuf>,
+    /// Accepted types as given by the page: MIME types, `.ext` extensions
+    /// or `Description|.ext1;.ext2` groups
+    pub filters: Vec<String>,
+    callback: FileDialogCallback,
+}
+
+impl FileDialog {
+    pub fn new(
+        mode: FileDialogMode,
+        title: Option<String>,
+        default_path: Option<PathBuf>,
+        filters: Vec<String>,
+        callback: FileDialogCallback,
+    ) -> Self {
+        Self {
+            mode,
+            title,
+            default_path,
+            filters,
+            callback,
+        }
+    }
+
+    pub fn select(self, paths: Vec<PathBuf>) {
+        let mut file_paths = CefStringList::new();
+        for path in paths.iter().filter_map(|path| path.to_str()) {
+            file_paths.append(path);
+        }
+
+        self.callback.cont(Some(&mut file_paths));
+    }
+
+    pub fn cancel(self) {
+        self.callback.cancel();
+    }
+}
+
+impl fmt::Debug for FileDialog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileDialog")
+            .field("mode", &self.mode)
+            .field("title", &self.title)
+            .field("default_path", &self.default_path)
+            .field("filters", &self.filters)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A download waiting for its destination, started once with `accept`
+///
+/// Dropping the request without accepting it cancels the download.
+pub struct DownloadRequest {
+    pub id: u32,
+    pub suggested_name: String,
+    callback: BeforeDownloadCallback,
+}
+
+impl DownloadRequest {
+    pub fn new(id: u32, suggested_name: String, callback: BeforeDownloadCallback) -> Self {
+        Self {
+            id,
+            suggested_name,
+            callback,
+        }
+    }
+
+    pub fn accept(self, path: &Path) {
+        let path = CefString::from(path.to_string_lossy().as_ref());
+        self.callback.cont(Some(&path), false.into());
+    }
+}
+
+impl fmt::Debug for DownloadRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DownloadRequest")
+            .field("id", &self.id)
+            .field("suggested_name", &self.suggested_name)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadState {
+    InProgress,
+    Complete,
+    Canceled,
+    Interrupted,
+}
+
+/// Progress of a running download
+pub struct Download {
+    pub id: u32,
+    pub path: Option<PathBuf>,
+    pub received_bytes: i64,
+    /// Total size in bytes, zero or negative when unknown
+    pub total_bytes: i64,
+    pub state: DownloadState,
+    callback: DownloadItemCallback,
+}
+
+impl Download {
+    pub fn new(
+        id: u32,
+        path: Option<PathBuf>,
+        received_bytes: i64,
+        total_bytes: i64,
+        state: DownloadState,
+        callback: DownloadItemCallback,
+    ) -> Self {
+        Self {
+            id,
+            path,
+            received_bytes,
+            total_bytes,
+            state,
+            callback,
+        }
+    }
+
+    /// Completed fraction between 0 and 1, if the total size is known
+    pub fn fraction(&self) -> Option<f64> {
+        (self.total_bytes > 0).then(|| self.received_bytes as f64 / self.total_bytes as f64)
+    }
+
+    pub fn file_name(&self) -> Option<String> {
+        self.path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+    }
+
+    pub fn cancel(&self) {
+        self.callback.cancel();
+    }
+}
+
+impl fmt::Debug for Download {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Download")
+            .field("id", &self.id)
+            .field("path", &self.path)
+            .field("received_bytes", &self.received_bytes)
+            .field("total_bytes", &self.total_bytes)
+            .field("state", &self.state)
+            .finish_non_exhaustive()
+    }
+}
+
+pub enum PermissionCallback {
+    Prompt(PermissionPromptCallback),
+    /// Media access with the requested bits
+    Media(MediaAccessCallback, u32),
+}
+
+/// Permissions asked by a page, answered once with `grant` or `deny`
+pub struct PermissionRequest {
+    pub origin: String,
+    pub permissions: Vec<Permission>,
+    callback: PermissionCallback,
+}
+
+impl PermissionRequest {
+    pub fn new(origin: String, permissions: Vec<Permission>, callback: PermissionCallback) -> Self {
+        Self {
+            origin,
+            permissions,
+            callback,
+        }
+    }
+
+    pub fn grant(self) {
+        match self.callback {
+            PermissionCallback::Prompt(callback) => {
+                callback.cont(CEF_PERMISSION_RESULT_ACCEPT.into())
+            }
+            PermissionCallback::Media(callback, requested) => callback.cont(requested),
+        }
+    }
+
+    pub fn deny(self) {
+        match self.callback {
+            PermissionCallback::Prompt(callback) => {
+                callback.cont(CEF_PERMISSION_RESULT_DENY.into())
+            }
+            PermissionCallback::Media(callback, _) => callback.cancel(),
+        }
+    }
+}
+
+impl fmt::Debug for PermissionRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PermissionRequest")
+            .field("origin", &self.origin)
+            .field("permissions", &self.permissions)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CertificateProblem {
+    CommonNameInvalid,
+    DateInvalid,
+    AuthorityInvalid,
+    Revoked,
+    Invalid,
+}
+
+/// A request with an invalid certificate, answered once with `allow` or `deny`
+pub struct CertificateError {
+    pub host: String,
+    pub url: String,
+    pub problem: CertificateProblem,
+    pub subject: Option<String>,
+    pub issuer: Option<String>,
+    /// SHA-256 fingerprint of the certificate, in hex
+    pub fingerprint: Option<String>,
+    callback: Callback,
+}
+
+impl CertificateError {
+    pub fn new(
+        host: String,
+        url: String,
+        problem: CertificateProblem,
+        subject: Option<String>,
+        issuer: Option<String>,
+        fingerprint: Option<String>,
+        callback: Callback,
+    ) -> Self {
+        Self {
+            host,
+            url,
+            problem,
+            subject,
+            issuer,
+            fingerprint,
+            callback,
+        }
+    }
+
+    pub fn allow(self) {
+        self.callback.cont();
+    }
+
+    pub fn deny(self) {
+        self.callback.cancel();
+    }
+}
+
+impl fmt::Debug for CertificateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CertificateError")
+            .field("host", &self.host)
+            .field("url", &self.url)
+            .field("problem", &self.problem)
+            .field("subject", &self.subject)
+            .field("issuer", &self.issuer)
+            .field("fingerprint", &self.fingerprint)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleLevel {
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConsoleMessage {
+    pub level: ConsoleLevel,
+    pub message: String,
+    pub source: String,
+    pub line: i32,
+}
+
+impl ConsoleMessage {
+    /// Logs the message under a `console::<severity>` target so page output can be filtered
+    pub fn log(&self) {
+        let Self {
+            message,
+            source,
+            line,
+            ..
+        } = self;
+
+        match self.level {
+            ConsoleLevel::Debug => {
+                tracing::debug!(target: "console::debug", "{message} ({source}:{line})")
+            }
+            ConsoleLevel::Info => {
+                tracing::info!(target: "console::info", "{message} ({source}:{line})")
+            }
+            ConsoleLevel::Warning => {
+                tracing::warn!(target: "console::warning", "{message} ({source}:{line})")
+            }
+            ConsoleLevel::Error => {
+                tracing::error!(target: "console::error", "{message} ({source}:{line})")
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Viewport {
     pub width: i32,
@@ -6,11 +351,11 @@ pub struct Viewport {
     pub zoom_level: f64,
 }
 
-impl Default for Viewport {
-    fn default() -> Self {
+impl Viewport {
+    pub fn new(width: i32, height: i32) -> Self {
         Self {
-            width: 1700,
-            height: 1004,
+            width,
+            height,
             scale_factor: 1,
             zoom_level: 0.0,
         }
@@ -0,0 +1,341 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+
+use cef::sys::{
+    cef_drag_operations_mask_t as OperationsMask, cef_key_event_type_t::KEYEVENT_CHAR,
+    cef_mouse_button_type_t::MBT_LEFT, cef_mouse_button_type_t::MBT_MIDDLE,
+    cef_mouse_button_type_t::MBT_RIGHT, cef_paint_element_type_t::PET_VIEW,
+};
+use cef::{Frame as MainFrame, *};
+
+use crate::chromium::{
+    config::{
+        MAX_FRAME_RATE, MAX_ZOOM_LEVEL, MIN_ZOOM_LEVEL, PRIMARY_PASTE_SCRIPT, ZOOM_LEVEL_FACTOR,
+    },
+    types::Viewport,
+};
+use crate::shared::{
+    states::{KeyboardState, PointerState, TouchPoint},
+    types::ClipboardAction,
+};
+
+/// An off-screen browser and the viewport it renders into, forwarding input from a `WebView`
+#[derive(Clone)]
+pub struct BrowserView {
+    browser: Arc<Mutex<Option<Browser>>>,
+    viewport: Arc<RwLock<Viewport>>,
+}
+
+impl BrowserView {
+    pub(super) fn new(
+        browser: Arc<Mutex<Option<Browser>>>,
+        viewport: Arc<RwLock<Viewport>>,
+    ) -> Self {
+        Self { browser, viewport }
+    }
+
+    /// Closes the browser without running unload handlers
+    pub fn close(&self) {
+        if let Some(browser_host) = self.browser_host() {
+            browser_host.close_browser(true.into());
+        }
+    }
+
+    pub fn scale_factor(&self) -> i32 {
+        self.viewport
+            .read()
+            .map(|viewport| viewport.scale_factor)
+            .unwrap_or(1)
+    }
+
+    pub fn load_url(&self, url: &str) {
+        if let Some(main_frame) = self.main_frame() {
+            let url = CefString::from(url);
+            main_frame.load_url(Some(&url));
+        }
+    }
+
+    pub fn set_monitor_info(&self, refresh_rate: f64, scale_factor: i32) {
+        if let Ok(mut viewport) = self.viewport.write() {
+            viewport.scale_factor = scale_factor;
+        }
+
+        if let Some(browser_host) = self.browser_host() {
+            browser_host.set_windowless_frame_rate(refresh_rate.min(MAX_FRAME_RATE) as i32);
+            browser_host.notify_screen_info_changed();
+        }
+    }
+
+    pub fn resize(&self, width: i32, height: i32) {
+        if let Ok(mut viewport) = self.viewport.write() {
+            viewport.width = width;
+            viewport.height = height;
+
+            if let Some(browser_host) = self.browser_host() {
+                browser_host.was_resized();
+                browser_host.invalidate(PET_VIEW.into());
+                // Re-apply zoom level on resize as it might reset
+                browser_host.set_zoom_level(viewport.zoom_level);
+            }
+        }
+    }
+
+    pub fn set_zoom(&self, level: f64) {
+        if let Ok(mut viewport) = self.viewport.write() {
+            viewport.zoom_level = level;
+        }
+
+        if let Some(browser_host) = self.browser_host() {
+            browser_host.set_zoom_level(level);
+        }
+    }
+
+    /// Zooms relative to the current level, used for pinch gestures
+    pub fn zoom_by(&self, factor: f64) {
+        let level = match self.viewport.read() {
+            Ok(viewport) => viewport.zoom_level,
+            Err(_) => return,
+        };
+
+        let level = level + factor.ln() / ZOOM_LEVEL_FACTOR.ln();
+        self.set_zoom(level.clamp(MIN_ZOOM_LEVEL, MAX_ZOOM_LEVEL));
+    }
+
+    pub fn hidden(&self, state: bool) {
+        if let Some(browser_host) = self.browser_host() {
+            browser_host.was_hidden(state.into());
+        }
+    }
+
+    pub fn focus(&self, state: bool) {
+        if let Some(browser_host) = self.browser_host() {
+            browser_host.set_focus(state.into());
+        }
+    }
+
+    pub fn forward_motion(&self, pointer_state: &PointerState) {
+        if let Some(browser_host) = self.browser_host() {
+            let mouse_event = MouseEvent::from(pointer_state);
+            let mouse_leave = (!pointer_state.over()).into();
+            browser_host.send_mouse_move_event(Some(&mouse_event), mouse_leave);
+        }
+    }
+
+    pub fn forward_scroll(&self, pointer_state: &PointerState, delta_x: i32, delta_y: i32) {
+        if let Some(browser_host) = self.browser_host() {
+            let mouse_event = MouseEvent::from(pointer_state);
+            browser_host.send_mouse_wheel_event(Some(&mouse_event), delta_x, delta_y);
+        }
+    }
+
+    pub fn forward_click(&self, pointer_state: &PointerState, count: i32) {
+        if let Some(browser_host) = self.browser_host() {
+            let pressed = pointer_state.pressed();
+            let button = pointer_state.button();
+
+            let mouse_event = MouseEvent::from(pointer_state);
+
+            let r#type = match button {
+                1 => Some(MBT_LEFT.into()),
+                2 => Some(MBT_MIDDLE.into()),
+                3 => Some(MBT_RIGHT.into()),
+                _ => None,
+            };
+
+            let mouse_up = (!pressed).into();
+
+            if let Some(r#type) = r#type {
+                browser_host.send_mouse_click_event(Some(&mouse_event), r#type, mouse_up, count);
+            }
+        }
+    }
+
+    pub fn forward_touch(&self, touch_point: &TouchPoint) {
+        if let Some(browser_host) = self.browser_host() {
+            let touch_event = TouchEvent::from(touch_point);
+            browser_host.send_touch_event(Some(&touch_event));
+        }
+    }
+
+    pub fn forward_key(&self, keyboard_state: &KeyboardState) {
+        if let Some(browser_host) = self.browser_host() {
+            if let Some(text) = keyboard_state.text()
+                && keyboard_state.pressed()
+                && !keyboard_state.composing()
+                && !keyboard_state.control_modifier()
+                && !keyboard_state.alt_modifier()
+            {
+                send_text(&browser_host, &text);
+            }
+
+            let key_event = KeyEvent::from(keyboard_state);
+            browser_host.send_key_event(Some(&key_event));
+        }
+    }
+
+    pub fn ime_set_composition(&self, text: &str, cursor: i32) {
+        if let Some(browser_host) = self.browser_host() {
+            if text.is_empty() {
+                browser_host.ime_cancel_composition();
+                return;
+            }
+
+            // GTK reports the cursor in characters, CEF expects UTF-16 code units
+            let cursor = text
+                .chars()
+                .take(cursor.max(0) as usize)
+                .map(char::len_utf16)
+                .sum::<usize>() as u32;
+
+            let composition = CefString::from(text);
+            let replacement_range = Range {
+                from: u32::MAX,
+                to: u32::MAX,
+            };
+            let selection_range = Range {
+                from: cursor,
+                to: cursor,
+            };
+
+            browser_host.ime_set_composition(
+                Some(&composition),
+                None,
+                Some(&replacement_range),
+                Some(&selection_range),
+            );
+        }
+    }
+
+    pub fn ime_commit_text(&self, text: &str) {
+        if let Some(browser_host) = self.browser_host() {
+            let text = CefString::from(text);
+            let replacement_range = Range {
+                from: u32::MAX,
+                to: u32::MAX,
+            };
+
+            browser_host.ime_commit_text(Some(&text), Some(&replacement_range), 0);
+        }
+    }
+
+    pub fn forward_file_enter(&self, pointer_state: &PointerState, path: PathBuf) {
+        if let Some(browser_host) = self.browser_host() {
+            let file_path = path.to_str().map(CefString::from);
+            let file_name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map(CefString::from);
+
+            if let Some(mut drag_data) = cef::drag_data_create() {
+                drag_data.add_file(file_path.as_ref(), file_name.as_ref());
+
+                let mouse_event = MouseEvent::from(pointer_state);
+
+                browser_host.drag_target_drag_enter(
+                    Some(&mut drag_data),
+                    Some(&mouse_event),
+                    OperationsMask::DRAG_OPERATION_MOVE.into(),
+                );
+            }
+        }
+    }
+
+    pub fn forward_file_hover(&self, pointer_state: &PointerState) {
+        if let Some(browser_host) = self.browser_host() {
+            let mouse_event = MouseEvent::from(pointer_state);
+
+            browser_host.drag_target_drag_over(
+                Some(&mouse_event),
+                OperationsMask::DRAG_OPERATION_MOVE.into(),
+            );
+        }
+    }
+
+    pub fn forward_file_drop(&self, pointer_state: &PointerState) {
+        if let Some(browser_host) = self.browser_host() {
+            let mouse_event = MouseEvent::from(pointer_state);
+
+            browser_host.drag_target_drop(Some(&mouse_event));
+        }
+    }
+
+    pub fn forward_file_leave(&self) {
+        if let Some(browser_host) = self.browser_host() {
+            browser_host.drag_target_drag_leave();
+        }
+    }
+
+    pub fn clipboard(&self, action: ClipboardAction) {
+        if let Some(main_frame) = self.main_frame() {
+            match action {
+                ClipboardAction::Copy => main_frame.copy(),
+                ClipboardAction::Cut => main_frame.cut(),
+                ClipboardAction::Paste => main_frame.paste(),
+                ClipboardAction::PasteAndMatchStyle => main_frame.paste_and_match_style(),
+            }
+        }
+    }
+
+    pub fn paste_primary(&self, pointer_state: &PointerState, text: &str) {
+        if let Some(main_frame) = self.main_frame()
+            && let Ok(text) = serde_json::to_string(text)
+        {
+            let (x, y) = pointer_state.position();
+
+            // The click itself was forwarded, the page only inserts into editable elements
+            let script = format!("({})({x}, {y}, {text});", PRIMARY_PASTE_SCRIPT.trim());
+            main_frame.execute_java_script(Some(&CefString::from(script.as_str())), None, 0);
+        }
+    }
+
+    pub fn go_back(&self) {
+        if let Ok(browser) = self.browser.lock()
+            && let Some(browser) = browser.as_ref()
+        {
+            browser.go_back();
+        }
+    }
+
+    pub fn go_forward(&self) {
+        if let Ok(browser) = self.browser.lock()
+            && let Some(browser) = browser.as_ref()
+        {
+            browser.go_forward();
+        }
+    }
+
+    pub(super) fn browser_host(&self) -> Option<BrowserHost> {
+        if let Ok(browser) = self.browser.lock()
+            && let Some(browser) = browser.as_ref()
+        {
+            return browser.host();
+        }
+
+        None
+    }
+
+    pub(super) fn main_frame(&self) -> Option<MainFrame> {
+        if let Ok(browser) = self.browser.lock()
+            && let Some(browser) = browser.as_ref()
+        {
+            return browser.main_frame();
+        }
+
+        None
+    }
+}
+
+/// Sends text as char events, one per UTF-16 code unit so characters
+/// outside of the BMP arrive as surrogate pairs
+fn send_text(browser_host: &BrowserHost, text: &str) {
+    for character in text.encode_utf16() {
+        let event = cef::KeyEvent {
+            type_: KEYEVENT_CHAR.into(),
+            character,
+            unmodified_character: character,
+            ..Default::default()
+        };
+
+        browser_host.send_key_event(Some(&event));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::shared::types::Permission;
+
 pub const DATA_DIR: &str = "stremio";
 
 pub const GETTEXT_DOMAIN: &str = "stremio";
@@ -5,3 +7,26 @@ pub const GETTEXT_DIR_DEV: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/po");
 pub const GETTEXT_DIR_FLATPAK: &str = "/app/share/locale";
 
 pub const STARTUP_URL: &str = "https://web.stremio.com";
+
+/// Scheme and host serving a local web UI bundle, used as startup url when one is found
+pub const WEB_UI_SCHEME: &str = "stremio-app";
+pub const WEB_UI_HOST: &str = "web";
+/// Directory or zip archive in the data dir looked up for a web UI bundle
+pub const WEB_UI_DIR: &str = "web-ui";
+pub const WEB_UI_ARCHIVE: &str = "web-ui.zip";
+
+pub const SETTINGS_FILE: &str = "settings.json";
+
+pub const DEFAULT_WINDOW_WIDTH: i32 = 1700;
+pub const DEFAULT_WINDOW_HEIGHT: i32 = 1050;
+/// Size requested by the window template, smaller saved sizes are grown to it
+pub const MIN_WINDOW_WIDTH: i32 = 400;
+pub const MIN_WINDOW_HEIGHT: i32 = 700;
+/// Saved sizes above this are treated as corrupted
+pub const MAX_WINDOW_SIZE: i32 = 16384;
+
+/// Permissions granted to every origin without asking, the web UI uses the local streaming server
+pub const DEFAULT_PERMISSIONS: &[Permission] = &[Permission::LocalNetworkAccess];
+
+/// Pixels scrolled per mouse wheel notch
+pub const DEFAULT_WHEEL_MULTIPLIER: f64 = 200.0;
@@ -1,17 +1,50 @@
 use discord_rich_presence::{DiscordIpc, DiscordIpcClient, activity};
+use gettextrs::gettext;
 use std::time::{SystemTime, UNIX_EPOCH};
+use url::Url;
+
+use crate::{
+    config::STARTUP_URL,
+    discord::config::{DEFAULT_STATE, IMDB_URL},
+    settings::{DiscordPrivacy, DiscordSettings},
+};
+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct DiscordMedia {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub image: Option<String>,
+    pub id: Option<String>,
+    pub kind: Option<String>,
+    /// Unknown until the metadata of the item arrived
+    pub adult: Option<bool>,
+}
 
 pub struct DiscordAdapter {
     client: DiscordIpcClient,
     connected: bool,
+    settings: DiscordSettings,
+    media: Option<DiscordMedia>,
+    /// When the current media item started, kept across updates of its metadata
+    started_at: i64,
+    /// Web UI the detail links point to
+    web_url: Url,
 }
 
 impl DiscordAdapter {
-    pub fn new(client_id: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let client = DiscordIpcClient::new(client_id);
+    /// `web_url` is the web UI the shell was started with
+    pub fn new(
+        settings: &DiscordSettings,
+        web_url: &str,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let client = DiscordIpcClient::new(&settings.client_id);
         Ok(Self {
             client,
             connected: false,
+            settings: settings.clone(),
+            media: None,
+            started_at: 0,
+            web_url: shared_web_url(web_url),
         })
     }
 
@@ -19,11 +52,15 @@ impl DiscordAdapter {
         self.connected
     }
 
+    pub fn client_id(&self) -> &str {
+        &self.settings.client_id
+    }
+
     pub fn connect(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         self.client.connect()?;
         self.connected = true;
         tracing::info!("Discord RPC Connected");
-        Ok(())
+        self.refresh()
     }
 
     pub fn disconnect(&mut self) -> Result<(), Box<dyn std::error::Error>> {
@@ -35,52 +72,121 @@ impl DiscordAdapter {
         Ok(())
     }
 
+    pub fn set_settings(
+        &mut self,
+        settings: &DiscordSettings,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.settings = settings.clone();
+        self.refresh()
+    }
+
     pub fn update_activity(
         &mut self,
-        title: Option<&str>,
-        artist: Option<&str>,
-        large_image_url: Option<&str>,
+        media: DiscordMedia,
     ) -> Result<(), Box<dyn std::error::Error>> {
+        if self.media.as_ref() == Some(&media) {
+            return Ok(());
+        }
+
+        if !is_same_item(self.media.as_ref(), &media) {
+            self.started_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        }
+
+        self.media = Some(media);
+        self.refresh()
+    }
+
+    /// Updates the title from the player, keeping what is already known about the item
+    pub fn update_title(&mut self, title: String) -> Result<(), Box<dyn std::error::Error>> {
+        let media = DiscordMedia {
+            title: Some(title),
+            ..self.media.clone().unwrap_or_default()
+        };
+
+        self.update_activity(media)
+    }
+
+    fn refresh(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         if !self.connected {
             return Ok(());
         }
 
-        let details = if let Some(t) = title {
-            if t.trim().is_empty() {
-                "Watching Video"
-            } else {
-                t
-            }
-        } else {
-            "Watching Video"
+        let Some(media) = self.media.clone() else {
+            return Ok(());
         };
 
-        // Discord requires state to be at least 2 chars if set.
-        let state = if let Some(a) = artist {
-            if a.trim().is_empty() { "Stremio" } else { a }
-        } else {
-            "Stremio"
+        let privacy = privacy(&self.settings, &media);
+
+        if privacy == DiscordPrivacy::Incognito {
+            tracing::info!("Discord RPC: activity hidden by privacy settings");
+            self.client.clear_activity()?;
+            return Ok(());
+        }
+
+        let details = match privacy {
+            DiscordPrivacy::Full => media
+                .title
+                .clone()
+                .filter(|title| !title.trim().is_empty())
+                .unwrap_or_else(|| gettext("Watching Video")),
+            _ => gettext("Watching something"),
         };
 
-        let start_time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        // Discord requires state to be at least 2 chars if set.
+        let state = match privacy {
+            DiscordPrivacy::Full => media
+                .artist
+                .as_deref()
+                .filter(|artist| !artist.trim().is_empty())
+                .unwrap_or(DEFAULT_STATE),
+            _ => DEFAULT_STATE,
+        };
 
         let mut activity = activity::Activity::new()
-            .details(details)
+            .details(&details)
             .state(state) // State is mandatory for most RP views or at least good practice
-            .timestamps(activity::Timestamps::new().start(start_time));
+            .timestamps(activity::Timestamps::new().start(self.started_at));
+
+        let large_image_url = media
+            .image
+            .as_deref()
+            .filter(|_| privacy == DiscordPrivacy::Full)
+            .filter(|url| !url.trim().is_empty());
+
+        if let Some(url) = large_image_url {
+            activity = activity.assets(
+                activity::Assets::new()
+                    .large_image(url)
+                    .large_text(&details),
+            );
+        }
+
+        let detail_url = detail_url(&self.web_url, &media);
+        let imdb_url = imdb_url(&media);
+        let detail_label = gettext("View on Stremio");
+        let imdb_label = gettext("View on IMDb");
 
-        if let Some(url) = large_image_url
-            && !url.trim().is_empty()
-        {
-            activity =
-                activity.assets(activity::Assets::new().large_image(url).large_text(details));
+        let mut buttons = vec![];
+        if self.settings.buttons && privacy == DiscordPrivacy::Full {
+            if let Some(url) = detail_url.as_deref() {
+                buttons.push(activity::Button::new(&detail_label, url));
+            }
+
+            if let Some(url) = imdb_url.as_deref() {
+                buttons.push(activity::Button::new(&imdb_label, url));
+            }
+        }
+
+        if !buttons.is_empty() {
+            activity = activity.buttons(buttons);
         }
 
         tracing::info!(
-            "Discord RPC Update: details='{}' state='{}' image='{:?}'",
+            "Discord RPC Update: details='{}' state='{}' image='{:?}' privacy={:?}",
             details,
             state,
-            large_image_url
+            large_image_url,
+            privacy
         );
         if let Err(e) = self.client.set_activity(activity) {
             tracing::error!("Discord RPC Failed to set activity: {:?}", e);
@@ -90,8 +196,235 @@ impl DiscordAdapter {
     }
 }
 
+/// Privacy level applied to the media, adult content is hidden regardless when asked to,
+/// as is media not known to be safe yet
+fn privacy(settings: &DiscordSettings, media: &DiscordMedia) -> DiscordPrivacy {
+    match settings.privacy {
+        DiscordPrivacy::Full if settings.hide_adult && media.adult != Some(false) => {
+            DiscordPrivacy::Incognito
+        }
+        privacy => privacy,
+    }
+}
+
+/// Whether an update is about the item already playing, the media title from the player
+/// comes first without an id and is then completed by the web UI
+fn is_same_item(current: Option<&DiscordMedia>, media: &DiscordMedia) -> bool {
+    let Some(current) = current else {
+        return false;
+    };
+
+    match (&current.id, &media.id) {
+        (Some(current), Some(id)) => current == id,
+        (None, Some(_)) => true,
+        _ => current.title == media.title,
+    }
+}
+
+/// Links can only point to a web UI others can open too, not to a bundled one
+fn shared_web_url(url: &str) -> Url {
+    Url::parse(url)
+        .ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https"))
+        .or_else(|| Url::parse(STARTUP_URL).ok())
+        .expect("STARTUP_URL is a valid url")
+}
+
+fn detail_url(web_url: &Url, media: &DiscordMedia) -> Option<String> {
+    let id = media.id.as_deref()?;
+    let kind = media.kind.as_deref()?;
+
+    let mut url = web_url.clone();
+    url.set_fragment(Some(&format!("/detail/{kind}/{id}")));
+    Some(url.into())
+}
+
+fn imdb_url(media: &DiscordMedia) -> Option<String> {
+    let id = media.id.as_deref()?.split(':').next()?;
+
+    id.starts_with("tt").then(|| format!("{IMDB_URL}/{id}"))
+}
+
 impl Drop for DiscordAdapter {
     fn drop(&mut self) {
         let _ = self.disconnect();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn media(id: Option<&str>, kind: Option<&str>, title: Option<&str>) -> DiscordMedia {
+        DiscordMedia {
+            id: id.map(str::to_owned),
+            kind: kind.map(str::to_owned),
+            title: title.map(str::to_owned),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn detail_urls() {
+        let cases = [
+            (Some("tt0944947"), Some("series"), Some("/series/tt0944947")),
+            (
+                Some("tt0944947:1:2"),
+                Some("series"),
+                Some("/series/tt0944947:1:2"),
+            ),
+            (Some("kitsu:1"), Some("anime"), Some("/anime/kitsu:1")),
+            (Some("tt0944947"), None, None),
+            (None, Some("movie"), None),
+        ];
+
+        let web_url = shared_web_url(STARTUP_URL);
+        for (id, kind, expected) in cases {
+            let expected = expected.map(|path| format!("https://web.stremio.com/#/detail{path}"));
+            assert_eq!(
+                detail_url(&web_url, &media(id, kind, None)),
+                expected,
+                "{id:?} {kind:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn web_urls() {
+        let cases = [
+            ("https://web.stremio.com", "https://web.stremio.com/"),
+            (
+                "http://127.0.0.1:8080/#/board",
+                "http://127.0.0.1:8080/#/board",
+            ),
+            ("stremio-app://web/", "https://web.stremio.com/"),
+            ("not an url", "https://web.stremio.com/"),
+        ];
+
+        for (url, expected) in cases {
+            assert_eq!(shared_web_url(url).as_str(), expected, "{url}");
+        }
+
+        let item = media(Some("tt1"), Some("movie"), None);
+        assert_eq!(
+            detail_url(&shared_web_url("http://127.0.0.1:8080/#/board"), &item).as_deref(),
+            Some("http://127.0.0.1:8080/#/detail/movie/tt1")
+        );
+    }
+
+    #[test]
+    fn imdb_urls() {
+        let cases = [
+            (Some("tt0944947"), Some("/tt0944947")),
+            (Some("tt0944947:1:2"), Some("/tt0944947")),
+            (Some("kitsu:1"), None),
+            (Some(""), None),
+            (None, None),
+        ];
+
+        for (id, expected) in cases {
+            let expected = expected.map(|path| format!("{IMDB_URL}{path}"));
+            assert_eq!(imdb_url(&media(id, None, None)), expected, "{id:?}");
+        }
+    }
+
+    #[test]
+    fn privacy_levels() {
+        let cases = [
+            (
+                DiscordPrivacy::Full,
+                Some(false),
+                true,
+                DiscordPrivacy::Full,
+            ),
+            (
+                DiscordPrivacy::Full,
+                Some(true),
+                true,
+                DiscordPrivacy::Incognito,
+            ),
+            (DiscordPrivacy::Full, None, true, DiscordPrivacy::Incognito),
+            (
+                DiscordPrivacy::Full,
+                Some(true),
+                false,
+                DiscordPrivacy::Full,
+            ),
+            (DiscordPrivacy::Full, None, false, DiscordPrivacy::Full),
+            (
+                DiscordPrivacy::Generic,
+                Some(true),
+                true,
+                DiscordPrivacy::Generic,
+            ),
+            (
+                DiscordPrivacy::Incognito,
+                Some(false),
+                false,
+                DiscordPrivacy::Incognito,
+            ),
+        ];
+
+        for (level, adult, hide_adult, expected) in cases {
+            let settings = DiscordSettings {
+                privacy: level,
+                hide_adult,
+                ..Default::default()
+            };
+            let media = DiscordMedia {
+                adult,
+                ..Default::default()
+            };
+
+            assert_eq!(
+                privacy(&settings, &media),
+                expected,
+                "{level:?} {adult:?} {hide_adult}"
+            );
+        }
+    }
+
+    #[test]
+    fn same_items() {
+        let title = media(None, None, Some("Title"));
+        let other_title = media(None, None, Some("Other"));
+        let item = media(Some("tt1"), Some("movie"), Some("Title"));
+        let other_item = media(Some("tt2"), Some("movie"), Some("Title"));
+
+        assert!(!is_same_item(None, &title));
+        assert!(is_same_item(Some(&title), &title));
+        assert!(!is_same_item(Some(&title), &other_title));
+        assert!(is_same_item(Some(&title), &item));
+        assert!(is_same_item(Some(&item), &item));
+        assert!(!is_same_item(Some(&item), &other_item));
+        assert!(!is_same_item(Some(&item), &other_title));
+    }
+
+    #[test]
+    fn titles() {
+        let mut adapter = DiscordAdapter::new(&Default::default(), STARTUP_URL).unwrap();
+
+        let item = DiscordMedia {
+            adult: Some(true),
+            ..media(Some("tt1"), Some("movie"), Some("Title"))
+        };
+        adapter.update_activity(item.clone()).unwrap();
+        adapter.started_at = 1;
+
+        // The player title of the same item keeps what the web UI told about it
+        adapter.update_title("Title.1080p.mkv".to_owned()).unwrap();
+        assert_eq!(
+            adapter.media,
+            Some(DiscordMedia {
+                title: Some("Title.1080p.mkv".to_owned()),
+                ..item
+            })
+        );
+        assert_eq!(adapter.started_at, 1);
+
+        // Nothing is known about a title alone
+        let mut adapter = DiscordAdapter::new(&Default::default(), STARTUP_URL).unwrap();
+        adapter.update_title("Title".to_owned()).unwrap();
+        assert_eq!(adapter.media.as_ref().and_then(|media| media.adult), None);
+    }
+}
@@ -0,0 +1,5 @@
+pub const CLIENT_ID: &str = "1450906751607111781";
+
+pub const IMDB_URL: &str = "https://www.imdb.com/title";
+
+pub const DEFAULT_STATE: &str = "Stremio";
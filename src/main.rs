@@ -4,10 +4,11 @@ mod config;
 mod discord;
 mod mpris;
 mod server;
+mod settings;
 mod shared;
 mod utils;
 
-use std::{env, fs, ptr};
+use std::{env, fs, path::PathBuf, ptr};
 
 use clap::Parser;
 use gtk::glib::{ExitCode, object::ObjectExt};
@@ -21,8 +22,14 @@ static GLOBAL: MiMalloc = MiMalloc;
 use crate::{
     app::Application,
     chromium::Chromium,
-    config::{DATA_DIR, GETTEXT_DIR_DEV, GETTEXT_DIR_FLATPAK, GETTEXT_DOMAIN, STARTUP_URL},
+    config::{
+        DATA_DIR, GETTEXT_DIR_DEV, GETTEXT_DIR_FLATPAK, GETTEXT_DOMAIN, STARTUP_URL,
+        WEB_UI_ARCHIVE, WEB_UI_DIR, WEB_UI_HOST, WEB_UI_SCHEME,
+    },
+    mpris::metadata,
     server::Server,
+    settings::Settings,
+    shared::proxy::Proxy,
 };
 
 #[derive(Parser, Debug)]
@@ -31,15 +38,24 @@ struct Args {
     /// Open dev tools
     #[arg(short, long)]
     dev: bool,
-    /// Startup url
-    #[arg(short, long, default_value = STARTUP_URL)]
-    url: String,
+    /// Startup url, defaults to the local web UI when there is one, else the hosted one
+    #[arg(short, long)]
+    url: Option<String>,
+    /// Serve the web UI from a directory or zip archive instead of the one in the data dir
+    #[arg(long, value_name = "PATH")]
+    web_ui: Option<PathBuf>,
     /// Open a deeplink
     #[arg(short, long)]
     open: Option<String>,
     /// Disable window decorations
     #[arg(short, long)]
     no_window_decorations: bool,
+    /// Add a Chromium switch as name[=value], or remove a default one with !name
+    #[arg(long = "chromium-flag", value_name = "FLAG")]
+    chromium_flags: Vec<String>,
+    /// Keep web security and certificate checks on and disable GPU acceleration flags
+    #[arg(long)]
+    safe_mode: bool,
 }
 
 fn main() -> ExitCode {
@@ -51,7 +67,35 @@ fn main() -> ExitCode {
 
     fs::create_dir_all(&data_dir).expect("Failed to create data directory");
 
-    let mut chromium = Chromium::new(&data_dir);
+    let settings = Settings::load(&data_dir);
+
+    let args = Args::parse();
+
+    // A bundle is only picked up from the data dir when it looks like one
+    let web_ui = args.web_ui.clone().or_else(|| {
+        let dir = data_dir.join(WEB_UI_DIR);
+        let archive = data_dir.join(WEB_UI_ARCHIVE);
+
+        if dir.join("index.html").is_file() {
+            Some(dir)
+        } else {
+            archive.is_file().then_some(archive)
+        }
+    });
+
+    let startup_url = match (&args.url, &web_ui) {
+        (Some(url), _) => url.clone(),
+        (None, Some(_)) => format!("{WEB_UI_SCHEME}://{WEB_UI_HOST}/"),
+        (None, None) => STARTUP_URL.to_owned(),
+    };
+
+    let mut chromium = Chromium::new(
+        &data_dir,
+        &settings,
+        &args.chromium_flags,
+        args.safe_mode,
+        web_ui.as_deref(),
+    );
     if let Some(exit_code) = chromium.execute() {
         return ExitCode::from(exit_code as u8);
     }
@@ -75,18 +119,23 @@ fn main() -> ExitCode {
             .unwrap_or(ptr::null())
     });
 
-    let args = Args::parse();
-
     let runtime = Runtime::new().expect("Failed to create Tokio runtime");
 
+    let proxy = runtime.block_on(Proxy::resolve(&settings.proxy));
+    metadata::set_proxy(&proxy);
+    chromium.set_proxy(proxy.clone());
+
     let mut server = Server::new();
-    server.start(args.dev).expect("Failed to start server");
+    server
+        .start(args.dev, &proxy)
+        .expect("Failed to start server");
 
     let app = Application::new();
     app.set_property("dev-mode", args.dev);
-    app.set_property("startup-url", args.url);
+    app.set_property("startup-url", startup_url);
     app.set_property("open-uri", args.open);
     app.set_property("decorations", !args.no_window_decorations);
+    app.set_settings(settings);
     app.set_browser(chromium);
 
     runtime.block_on(app.run())
@@ -56,6 +56,10 @@ impl MprisAdapter {
         self.controller.update_position(position);
     }
 
+    pub fn update_next_video(&self, available: bool) {
+        self.controller.update_next_video(available);
+    }
+
     pub fn update_metadata_simple(
         &self,
         title: Option<String>,
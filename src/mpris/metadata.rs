@@ -1,6 +1,32 @@
-use crate::shared::types::UserEvent;
+use crate::shared::{proxy::Proxy, types::UserEvent};
+use anyhow::Context;
 use flume::Sender;
-use std::thread;
+use reqwest::blocking::Client;
+use std::{sync::OnceLock, thread};
+
+const ADULT_GENRES: &[&str] = &["adult", "erotic", "porn"];
+
+/// Unset when the proxy can't be used, metadata is not fetched then
+static CLIENT: OnceLock<Option<Client>> = OnceLock::new();
+
+/// Sends the metadata requests through the proxy, must be called before any fetch
+pub fn set_proxy(proxy: &Proxy) {
+    let client = proxy
+        .http_client()
+        .map_err(|e| tracing::error!("Unsupported proxy, metadata requests are disabled: {e}"))
+        .ok();
+
+    CLIENT.set(client).ok();
+}
+
+fn get(url: &str) -> anyhow::Result<reqwest::blocking::Response> {
+    let client = CLIENT
+        .get_or_init(|| Some(Client::new()))
+        .as_ref()
+        .context("Metadata requests are disabled")?;
+
+    Ok(client.get(url).send()?)
+}
 
 pub fn fetch_metadata(title: String, event_sender: Sender<UserEvent>) {
     thread::spawn(move || {
@@ -18,7 +44,7 @@ pub fn fetch_metadata(title: String, event_sender: Sender<UserEvent>) {
                         .collect::<String>()
                 );
 
-                if let Ok(resp) = reqwest::blocking::get(&search_url)
+                if let Ok(resp) = get(&search_url)
                     && let Ok(json) = resp.json::<serde_json::Value>()
                     && let Some(metas) = json["metas"].as_array()
                     && !metas.is_empty()
@@ -29,7 +55,7 @@ pub fn fetch_metadata(title: String, event_sender: Sender<UserEvent>) {
                         // Now fetch full meta
                         let meta_url =
                             format!("https://v3-cinemeta.strem.io/meta/series/{}.json", id);
-                        if let Ok(resp) = reqwest::blocking::get(&meta_url)
+                        if let Ok(resp) = get(&meta_url)
                             && let Ok(json) = resp.json::<serde_json::Value>()
                         {
                             let meta = &json["meta"];
@@ -74,6 +100,9 @@ pub fn fetch_metadata(title: String, event_sender: Sender<UserEvent>) {
                                         poster,
                                         thumbnail: thumb_url,
                                         logo: logo.clone(),
+                                        id: Some(id.to_string()),
+                                        kind: Some("series".to_string()),
+                                        adult: Some(is_adult(meta)),
                                     })
                                     .ok();
                             }
@@ -89,7 +118,7 @@ pub fn fetch_metadata(title: String, event_sender: Sender<UserEvent>) {
             );
             tracing::info!("Cinemeta: Searching for movie '{}': {}", title, search_url);
 
-            match reqwest::blocking::get(&search_url) {
+            match get(&search_url) {
                 Ok(resp) => {
                     match resp.json::<serde_json::Value>() {
                         Ok(json) => {
@@ -105,7 +134,7 @@ pub fn fetch_metadata(title: String, event_sender: Sender<UserEvent>) {
                                         "https://v3-cinemeta.strem.io/meta/movie/{}.json",
                                         meta_id
                                     );
-                                    match reqwest::blocking::get(&meta_url) {
+                                    match get(&meta_url) {
                                         Ok(resp) => {
                                             if let Ok(json) = resp.json::<serde_json::Value>() {
                                                 tracing::info!(
@@ -136,6 +165,9 @@ pub fn fetch_metadata(title: String, event_sender: Sender<UserEvent>) {
                                                         poster,
                                                         thumbnail: background,
                                                         logo,
+                                                        id: Some(meta_id.to_string()),
+                                                        kind: Some("movie".to_string()),
+                                                        adult: Some(is_adult(meta)),
                                                     })
                                                     .ok();
                                             } else {
@@ -176,7 +208,7 @@ pub fn fetch_metadata_by_sid(sid: String, event_sender: Sender<UserEvent>) {
             };
 
             let url = format!("https://v3-cinemeta.strem.io/meta/{}/{}.json", type_str, id);
-            if let Ok(resp) = reqwest::blocking::get(&url)
+            if let Ok(resp) = get(&url)
                 && let Ok(json) = resp.json::<serde_json::Value>()
             {
                 tracing::info!("SID Metadata Fetch Success");
@@ -230,9 +262,26 @@ pub fn fetch_metadata_by_sid(sid: String, event_sender: Sender<UserEvent>) {
                         poster,
                         thumbnail,
                         logo,
+                        id: Some(id.to_string()),
+                        kind: Some(type_str.to_string()),
+                        adult: Some(is_adult(meta)),
                     })
                     .ok();
             }
         });
     }
 }
+
+fn is_adult(meta: &serde_json::Value) -> bool {
+    meta["genres"]
+        .as_array()
+        .is_some_and(|genres| adult_genres(genres.iter().filter_map(|genre| genre.as_str())))
+}
+
+/// Whether any of the genres marks adult content, the only rule for metadata from either
+/// the addons or the web UI
+pub fn adult_genres<'a>(genres: impl IntoIterator<Item = &'a str>) -> bool {
+    genres
+        .into_iter()
+        .any(|genre| ADULT_GENRES.contains(&genre.trim().to_lowercase().as_str()))
+}
@@ -18,6 +18,8 @@ struct MprisState {
     art_url: Option<String>,
     media_duration: Option<f64>,
     media_position: Option<f64>,
+    /// Whether the web app has a video to play next
+    has_next: bool,
 }
 
 impl Default for MprisState {
@@ -33,6 +35,7 @@ impl Default for MprisState {
             art_url: None,
             media_duration: None,
             media_position: None,
+            has_next: false,
         }
     }
 }
@@ -41,6 +44,7 @@ impl Default for MprisState {
 pub enum MprisStateUpdate {
     PlaybackStatus,
     Metadata,
+    CanGoNext,
 }
 
 pub struct MprisController {
@@ -88,6 +92,12 @@ impl MprisController {
         let mut state = self.state.write().unwrap();
         state.media_position = Some(position);
     }
+
+    pub fn update_next_video(&self, available: bool) {
+        let mut state = self.state.write().unwrap();
+        state.has_next = available;
+        self.update_tx.send(MprisStateUpdate::CanGoNext).ok();
+    }
 }
 
 fn build_metadata(state: &MprisState) -> HashMap<String, zbus::zvariant::OwnedValue> {
@@ -302,6 +312,9 @@ impl MprisPlayerImpl {
     #[zbus(property)]
     fn set_volume(&self, volume: f64) {
         self.state.write().unwrap().volume = volume;
+        self.proxy
+            .send(UserEvent::MprisCommand(MprisCommand::SetVolume(volume)))
+            .ok();
     }
 
     #[zbus(property)]
@@ -325,7 +338,8 @@ impl MprisPlayerImpl {
 
     #[zbus(property)]
     fn can_go_next(&self) -> bool {
-        false
+        let state = self.state.read().unwrap();
+        state.has_next && state.playback_status != "Stopped"
     }
 
     #[zbus(property)]
@@ -413,12 +427,10 @@ pub fn start_mpris_service(proxy: Sender<UserEvent>) -> MprisController {
                             .await
                         {
                             let ctxt = iface_ref.signal_context();
-                            iface_ref
-                                .get()
-                                .await
-                                .playback_status_changed(ctxt)
-                                .await
-                                .ok();
+                            let iface = iface_ref.get().await;
+                            iface.playback_status_changed(ctxt).await.ok();
+                            // Depends on whether anything is playing
+                            iface.can_go_next_changed(ctxt).await.ok();
                         }
                     }
                     MprisStateUpdate::Metadata => {
@@ -430,6 +442,15 @@ pub fn start_mpris_service(proxy: Sender<UserEvent>) -> MprisController {
                             iface_ref.get().await.metadata_changed(ctxt).await.ok();
                         }
                     }
+                    MprisStateUpdate::CanGoNext => {
+                        if let Ok(iface_ref) = object_server
+                            .interface::<_, MprisPlayerImpl>("/org/mpris/MediaPlayer2")
+                            .await
+                        {
+                            let ctxt = iface_ref.signal_context();
+                            iface_ref.get().await.can_go_next_changed(ctxt).await.ok();
+                        }
+                    }
                 }
             }
         });
@@ -9,6 +9,8 @@ use std::{
 use anyhow::{Context, Ok};
 use tracing::debug;
 
+use crate::shared::proxy::Proxy;
+
 pub struct Server {
     process: Option<Child>,
     file: PathBuf,
@@ -25,8 +27,17 @@ impl Server {
         }
     }
 
-    pub fn start(&mut self, dev: bool) -> anyhow::Result<()> {
-        let mut child = Command::new("node")
+    pub fn start(&mut self, dev: bool, proxy: &Proxy) -> anyhow::Result<()> {
+        let mut command = Command::new("node");
+
+        for (name, value) in proxy.env() {
+            match value {
+                Some(value) => command.env(name, value),
+                None => command.env_remove(name),
+            };
+        }
+
+        let mut child = command
             .env("NO_CORS", (dev as i32).to_string())
             .arg(self.file.as_os_str())
             .stdout(process::Stdio::piped())
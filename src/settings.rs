@@ -0,0 +1,413 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::{
+    config::{
+        DEFAULT_PERMISSIONS, DEFAULT_WHEEL_MULTIPLIER, DEFAULT_WINDOW_HEIGHT, DEFAULT_WINDOW_WIDTH,
+        MAX_WINDOW_SIZE, MIN_WINDOW_HEIGHT, MIN_WINDOW_WIDTH, SETTINGS_FILE,
+    },
+    discord::config::CLIENT_ID,
+    shared::types::Permission,
+};
+
+/// Lists the variants of a settings enum in the order of its combo row
+macro_rules! positions {
+    ($name:ident { $($variant:ident),+ $(,)? }) => {
+        impl $name {
+            pub const ALL: &[$name] = &[$(Self::$variant),+];
+
+            pub fn position(&self) -> u32 {
+                Self::ALL
+                    .iter()
+                    .position(|it| it == self)
+                    .unwrap_or_default() as u32
+            }
+
+            pub fn from_position(position: u32) -> Self {
+                Self::ALL
+                    .get(position as usize)
+                    .copied()
+                    .unwrap_or_default()
+            }
+        }
+    };
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiscordPrivacy {
+    /// Show the title, artwork and episode of what is playing
+    #[default]
+    Full,
+    /// Only show that something is being watched
+    Generic,
+    /// Do not show any activity
+    Incognito,
+}
+
+positions!(DiscordPrivacy {
+    Full,
+    Generic,
+    Incognito
+});
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum CloseAction {
+    /// Keep running in the background, reachable from the tray icon
+    #[default]
+    HideToTray,
+    /// Quit the application
+    Quit,
+    /// Ask every time the window is closed
+    Ask,
+}
+
+positions!(CloseAction {
+    HideToTray,
+    Quit,
+    Ask
+});
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProxyMode {
+    /// Use the proxy configured in the system, resolved through the portal
+    #[default]
+    System,
+    /// Connect without a proxy
+    Direct,
+    /// Use the HTTP or SOCKS5 proxy from the settings
+    Manual,
+    /// Use the proxy auto-config script from the settings
+    Pac,
+}
+
+positions!(ProxyMode {
+    System,
+    Direct,
+    Manual,
+    Pac
+});
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct WindowSettings {
+    pub close_action: CloseAction,
+    pub width: i32,
+    pub height: i32,
+    pub maximized: bool,
+    pub fullscreen: bool,
+    /// Connector of the monitor the window was last shown on
+    pub monitor: Option<String>,
+    /// Keep the window in the aspect ratio of the playing video
+    pub lock_aspect_ratio: bool,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            close_action: CloseAction::default(),
+            width: DEFAULT_WINDOW_WIDTH,
+            height: DEFAULT_WINDOW_HEIGHT,
+            maximized: false,
+            fullscreen: false,
+            monitor: None,
+            lock_aspect_ratio: false,
+        }
+    }
+}
+
+impl WindowSettings {
+    /// Replaces sizes no window could have, like from a hand edited or corrupted file
+    fn sanitize(&mut self) {
+        self.width = window_size(self.width, MIN_WINDOW_WIDTH, DEFAULT_WINDOW_WIDTH);
+        self.height = window_size(self.height, MIN_WINDOW_HEIGHT, DEFAULT_WINDOW_HEIGHT);
+    }
+}
+
+fn window_size(size: i32, min: i32, default: i32) -> i32 {
+    match size {
+        ..=0 => default,
+        size if size > MAX_WINDOW_SIZE => default,
+        size => size.max(min),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct InputSettings {
+    /// Pixels scrolled per mouse wheel notch
+    pub wheel_multiplier: f64,
+    /// Keep scrolling after a touchpad fling
+    pub kinetic_scrolling: bool,
+    /// Navigate back and forward with the extra mouse buttons
+    pub navigation_buttons: bool,
+}
+
+impl Default for InputSettings {
+    fn default() -> Self {
+        Self {
+            wheel_multiplier: DEFAULT_WHEEL_MULTIPLIER,
+            kinetic_scrolling: true,
+            navigation_buttons: true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct ProxySettings {
+    pub mode: ProxyMode,
+    /// Manual proxy, like `http://host:port` or `socks5://host:port`
+    pub url: String,
+    /// Location of the proxy auto-config script
+    pub pac_url: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct ChromiumSettings {
+    /// Keep web security and certificate checks on and skip the GPU switches
+    pub safe_mode: bool,
+    /// Extra switches as `name[=value]`, or `!name` to remove a default one
+    pub switches: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct DiscordSettings {
+    pub enabled: bool,
+    pub client_id: String,
+    pub privacy: DiscordPrivacy,
+    pub hide_adult: bool,
+    pub buttons: bool,
+}
+
+impl Default for DiscordSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            client_id: CLIENT_ID.to_owned(),
+            privacy: DiscordPrivacy::default(),
+            hide_adult: true,
+            buttons: true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CertificateDecision {
+    /// SHA-256 fingerprint of the certificate
+    pub fingerprint: String,
+    pub allowed: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct Settings {
+    #[serde(skip)]
+    path: PathBuf,
+    pub window: WindowSettings,
+    pub input: InputSettings,
+    pub chromium: ChromiumSettings,
+    pub proxy: ProxySettings,
+    /// Remembered answers to certificate errors per host, only for the certificate answered
+    pub certificates: BTreeMap<String, CertificateDecision>,
+    /// Remembered permissions per origin, `true` when granted
+    pub permissions: BTreeMap<String, BTreeMap<Permission, bool>>,
+    pub discord: DiscordSettings,
+}
+
+impl Settings {
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(SETTINGS_FILE);
+
+        let mut settings = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| {
+                serde_json::from_str::<Settings>(&content)
+                    .map_err(|e| error!("Failed to parse settings: {e}"))
+                    .ok()
+            })
+            .unwrap_or_default();
+
+        settings.window.sanitize();
+        settings.path = path;
+        settings
+    }
+
+    /// Remembered answer for all the permissions asked by an origin,
+    /// `None` when the user has to be asked
+    pub fn permission_decision(&self, origin: &str, permissions: &[Permission]) -> Option<bool> {
+        let remembered = self.permissions.get(origin);
+
+        let mut decision = Some(true);
+        for permission in permissions {
+            let granted = match remembered.and_then(|remembered| remembered.get(permission)) {
+                Some(granted) => Some(*granted),
+                None if DEFAULT_PERMISSIONS.contains(permission) => Some(true),
+                None => None,
+            };
+
+            match granted {
+                Some(false) => return Some(false),
+                Some(true) => {}
+                None => decision = None,
+            }
+        }
+
+        decision
+    }
+
+    /// Remembered answer for a certificate error, `None` when the host
+    /// was never answered or its certificate changed since
+    pub fn certificate_decision(&self, host: &str, fingerprint: Option<&str>) -> Option<bool> {
+        let decision = self.certificates.get(host)?;
+
+        (Some(decision.fingerprint.as_str()) == fingerprint).then_some(decision.allowed)
+    }
+
+    pub fn remember_certificate(&mut self, host: &str, fingerprint: &str, allowed: bool) {
+        let decision = CertificateDecision {
+            fingerprint: fingerprint.to_owned(),
+            allowed,
+        };
+
+        self.certificates.insert(host.to_owned(), decision);
+    }
+
+    pub fn remember_permission(&mut self, origin: &str, permission: Permission, granted: bool) {
+        if permission == Permission::Other {
+            return;
+        }
+
+        self.permissions
+            .entry(origin.to_owned())
+            .or_default()
+            .insert(permission, granted);
+    }
+
+    pub fn save(&self) {
+        match serde_json::to_string_pretty(self) {
+            Ok(content) => {
+                fs::write(&self.path, content)
+                    .map_err(|e| error!("Failed to save settings: {e}"))
+                    .ok();
+            }
+            Err(e) => error!("Failed to serialize settings: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_sizes() {
+        let cases = [
+            (1280, 1280),
+            (MIN_WINDOW_WIDTH, MIN_WINDOW_WIDTH),
+            (100, MIN_WINDOW_WIDTH),
+            (0, DEFAULT_WINDOW_WIDTH),
+            (-50, DEFAULT_WINDOW_WIDTH),
+            (MAX_WINDOW_SIZE, MAX_WINDOW_SIZE),
+            (MAX_WINDOW_SIZE + 1, DEFAULT_WINDOW_WIDTH),
+            (i32::MAX, DEFAULT_WINDOW_WIDTH),
+        ];
+
+        for (size, expected) in cases {
+            let size = window_size(size, MIN_WINDOW_WIDTH, DEFAULT_WINDOW_WIDTH);
+            assert_eq!(size, expected);
+        }
+    }
+
+    #[test]
+    fn permission_decisions() {
+        let mut settings = Settings::default();
+        let origin = "https://example.com";
+
+        assert_eq!(
+            settings.permission_decision(origin, &[Permission::Camera]),
+            None
+        );
+        assert_eq!(
+            settings.permission_decision(origin, &[Permission::LocalNetworkAccess]),
+            Some(true)
+        );
+
+        settings.remember_permission(origin, Permission::Camera, true);
+        assert_eq!(
+            settings.permission_decision(origin, &[Permission::Camera]),
+            Some(true)
+        );
+        assert_eq!(
+            settings.permission_decision(origin, &[Permission::Camera, Permission::Microphone]),
+            None
+        );
+        assert_eq!(
+            settings.permission_decision("https://example.org", &[Permission::Camera]),
+            None
+        );
+
+        settings.remember_permission(origin, Permission::Microphone, false);
+        assert_eq!(
+            settings.permission_decision(origin, &[Permission::Camera, Permission::Microphone]),
+            Some(false)
+        );
+
+        settings.remember_permission(origin, Permission::LocalNetworkAccess, false);
+        assert_eq!(
+            settings.permission_decision(origin, &[Permission::LocalNetworkAccess]),
+            Some(false)
+        );
+
+        // Unknown permissions are asked every time
+        settings.remember_permission(origin, Permission::Other, true);
+        assert_eq!(
+            settings.permission_decision(origin, &[Permission::Other]),
+            None
+        );
+    }
+
+    #[test]
+    fn certificate_decisions() {
+        let mut settings = Settings::default();
+        assert_eq!(
+            settings.certificate_decision("example.com", Some("aa")),
+            None
+        );
+
+        settings.remember_certificate("example.com", "aa", true);
+        assert_eq!(
+            settings.certificate_decision("example.com", Some("aa")),
+            Some(true)
+        );
+        assert_eq!(
+            settings.certificate_decision("example.com", Some("bb")),
+            None
+        );
+        assert_eq!(settings.certificate_decision("example.com", None), None);
+        assert_eq!(
+            settings.certificate_decision("example.org", Some("aa")),
+            None
+        );
+
+        settings.remember_certificate("example.com", "bb", false);
+        assert_eq!(
+            settings.certificate_decision("example.com", Some("aa")),
+            None
+        );
+        assert_eq!(
+            settings.certificate_decision("example.com", Some("bb")),
+            Some(false)
+        );
+    }
+}
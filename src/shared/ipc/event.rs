@@ -19,6 +19,20 @@ pub struct MetadataUpdate {
     pub poster: Option<String>,
     pub thumbnail: Option<String>,
     pub logo: Option<String>,
+    pub id: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    /// Unset while the web UI doesn't know the item yet
+    pub genres: Option<Vec<String>>,
+    #[serde(rename = "nextVideo")]
+    pub next_video: Option<bool>,
+}
+
+/// Entry the web UI adds to the native context menu
+#[derive(Deserialize, Debug, Clone)]
+pub struct ContextMenuItem {
+    pub id: String,
+    pub label: String,
 }
 
 #[derive(Deserialize, Debug)]
@@ -27,10 +41,18 @@ pub enum IpcEvent {
     Ready,
     Quit,
     Fullscreen(bool),
+    MiniPlayer(bool),
     Visibility(bool),
     OpenMedia(String),
+    NextVideo,
     Mpv(IpcEventMpv),
     MetadataUpdate(MetadataUpdate),
+    ContextMenuItems(Vec<ContextMenuItem>),
+    ContextMenuAction(String),
+    /// An auxiliary window navigated to a new address while open
+    AuxiliaryNavigated(String),
+    /// An auxiliary window was closed, with the last address it showed
+    AuxiliaryClosed(String),
 }
 
 impl TryFrom<&str> for IpcEvent {
@@ -1,7 +1,7 @@
 use serde::Deserialize;
 use serde_json::Value;
 
-use super::event::{IpcEvent, IpcEventMpv, MetadataUpdate};
+use super::event::{ContextMenuItem, IpcEvent, IpcEventMpv, MetadataUpdate};
 
 #[derive(Deserialize, Debug)]
 pub struct IpcMessageRequest {
@@ -37,6 +37,10 @@ impl TryFrom<IpcMessageRequest> for IpcEvent {
 
                                 Ok(IpcEvent::Fullscreen(data.fullscreen))
                             }
+                            "win-set-mini-player" => {
+                                let active = data.as_bool().ok_or("Invalid win-set-mini-player")?;
+                                Ok(IpcEvent::MiniPlayer(active))
+                            }
                             "mpv-command" => {
                                 let data: Vec<String> = serde_json::from_value(data)
                                     .expect("Invalid mpv-command arguments");
@@ -74,6 +78,11 @@ impl TryFrom<IpcMessageRequest> for IpcEvent {
                                     .expect("Invalid metadata-update object");
                                 Ok(IpcEvent::MetadataUpdate(data))
                             }
+                            "context-menu-set-items" => {
+                                let items: Vec<ContextMenuItem> = serde_json::from_value(data)
+                                    .map_err(|_| "Invalid context-menu-set-items")?;
+                                Ok(IpcEvent::ContextMenuItems(items))
+                            }
                             _ => Err("Unknown method"),
                         },
                         None => match name {
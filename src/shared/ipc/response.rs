@@ -70,6 +70,38 @@ impl TryFrom<IpcEvent> for IpcMessageResponse {
                 data: None,
                 args: Some(json!(["open-media", deeplink])),
             }),
+            IpcEvent::NextVideo => Ok(IpcMessageResponse {
+                id: 1,
+                r#type: 1,
+                object: TRANSPORT_NAME.to_owned(),
+                data: None,
+                args: Some(json!(["next-video"])),
+            }),
+            IpcEvent::ContextMenuAction(id) => Ok(IpcMessageResponse {
+                id: 1,
+                r#type: 1,
+                object: TRANSPORT_NAME.to_owned(),
+                data: None,
+                args: Some(json!(["context-menu-action", id])),
+            }),
+            IpcEvent::AuxiliaryNavigated(url) => Ok(IpcMessageResponse {
+                id: 1,
+                r#type: 1,
+                object: TRANSPORT_NAME.to_owned(),
+                data: None,
+                args: Some(json!(["auxiliary-window-navigated", {
+                    "url": url,
+                }])),
+            }),
+            IpcEvent::AuxiliaryClosed(url) => Ok(IpcMessageResponse {
+                id: 1,
+                r#type: 1,
+                object: TRANSPORT_NAME.to_owned(),
+                data: None,
+                args: Some(json!(["auxiliary-window-closed", {
+                    "url": url,
+                }])),
+            }),
             IpcEvent::Mpv(IpcEventMpv::Change((name, value))) => Ok(IpcMessageResponse {
                 id: 1,
                 r#type: 1,
@@ -0,0 +1,338 @@
+use gtk::{gdk::Key, glib::translate::IntoGlib};
+
+/// Windows virtual key code sent for keys consumed by an input method
+pub const PROCESS_KEY: u32 = 0xE5;
+
+/// A physical key, identified by its X11 hardware keycode (evdev code + 8)
+struct PhysicalKey {
+    keycode: u32,
+    /// DOM `KeyboardEvent.code` of the key
+    code: &'static str,
+    /// Windows virtual key code of the key on a US layout
+    windows: u32,
+}
+
+const fn key(keycode: u32, code: &'static str, windows: u32) -> PhysicalKey {
+    PhysicalKey {
+        keycode,
+        code,
+        windows,
+    }
+}
+
+const PHYSICAL_KEYS: &[PhysicalKey] = &[
+    key(9, "Escape", 0x1B),
+    key(10, "Digit1", 0x31),
+    key(11, "Digit2", 0x32),
+    key(12, "Digit3", 0x33),
+    key(13, "Digit4", 0x34),
+    key(14, "Digit5", 0x35),
+    key(15, "Digit6", 0x36),
+    key(16, "Digit7", 0x37),
+    key(17, "Digit8", 0x38),
+    key(18, "Digit9", 0x39),
+    key(19, "Digit0", 0x30),
+    key(20, "Minus", 0xBD),
+    key(21, "Equal", 0xBB),
+    key(22, "Backspace", 0x08),
+    key(23, "Tab", 0x09),
+    key(24, "KeyQ", 0x51),
+    key(25, "KeyW", 0x57),
+    key(26, "KeyE", 0x45),
+    key(27, "KeyR", 0x52),
+    key(28, "KeyT", 0x54),
+    key(29, "KeyY", 0x59),
+    key(30, "KeyU", 0x55),
+    key(31, "KeyI", 0x49),
+    key(32, "KeyO", 0x4F),
+    key(33, "KeyP", 0x50),
+    key(34, "BracketLeft", 0xDB),
+    key(35, "BracketRight", 0xDD),
+    key(36, "Enter", 0x0D),
+    key(37, "ControlLeft", 0x11),
+    key(38, "KeyA", 0x41),
+    key(39, "KeyS", 0x53),
+    key(40, "KeyD", 0x44),
+    key(41, "KeyF", 0x46),
+    key(42, "KeyG", 0x47),
+    key(43, "KeyH", 0x48),
+    key(44, "KeyJ", 0x4A),
+    key(45, "KeyK", 0x4B),
+    key(46, "KeyL", 0x4C),
+    key(47, "Semicolon", 0xBA),
+    key(48, "Quote", 0xDE),
+    key(49, "Backquote", 0xC0),
+    key(50, "ShiftLeft", 0x10),
+    key(51, "Backslash", 0xDC),
+    key(52, "KeyZ", 0x5A),
+    key(53, "KeyX", 0x58),
+    key(54, "KeyC", 0x43),
+    key(55, "KeyV", 0x56),
+    key(56, "KeyB", 0x42),
+    key(57, "KeyN", 0x4E),
+    key(58, "KeyM", 0x4D),
+    key(59, "Comma", 0xBC),
+    key(60, "Period", 0xBE),
+    key(61, "Slash", 0xBF),
+    key(62, "ShiftRight", 0x10),
+    key(63, "NumpadMultiply", 0x6A),
+    key(64, "AltLeft", 0x12),
+    key(65, "Space", 0x20),
+    key(66, "CapsLock", 0x14),
+    key(67, "F1", 0x70),
+    key(68, "F2", 0x71),
+    key(69, "F3", 0x72),
+    key(70, "F4", 0x73),
+    key(71, "F5", 0x74),
+    key(72, "F6", 0x75),
+    key(73, "F7", 0x76),
+    key(74, "F8", 0x77),
+    key(75, "F9", 0x78),
+    key(76, "F10", 0x79),
+    key(77, "NumLock", 0x90),
+    key(78, "ScrollLock", 0x91),
+    key(79, "Numpad7", 0x67),
+    key(80, "Numpad8", 0x68),
+    key(81, "Numpad9", 0x69),
+    key(82, "NumpadSubtract", 0x6D),
+    key(83, "Numpad4", 0x64),
+    key(84, "Numpad5", 0x65),
+    key(85, "Numpad6", 0x66),
+    key(86, "NumpadAdd", 0x6B),
+    key(87, "Numpad1", 0x61),
+    key(88, "Numpad2", 0x62),
+    key(89, "Numpad3", 0x63),
+    key(90, "Numpad0", 0x60),
+    key(91, "NumpadDecimal", 0x6E),
+    key(94, "IntlBackslash", 0xE2),
+    key(95, "F11", 0x7A),
+    key(96, "F12", 0x7B),
+    key(104, "NumpadEnter", 0x0D),
+    key(105, "ControlRight", 0x11),
+    key(106, "NumpadDivide", 0x6F),
+    key(107, "PrintScreen", 0x2C),
+    key(108, "AltRight", 0x12),
+    key(110, "Home", 0x24),
+    key(111, "ArrowUp", 0x26),
+    key(112, "PageUp", 0x21),
+    key(113, "ArrowLeft", 0x25),
+    key(114, "ArrowRight", 0x27),
+    key(115, "End", 0x23),
+    key(116, "ArrowDown", 0x28),
+    key(117, "PageDown", 0x22),
+    key(118, "Insert", 0x2D),
+    key(119, "Delete", 0x2E),
+    key(121, "AudioVolumeMute", 0xAD),
+    key(122, "AudioVolumeDown", 0xAE),
+    key(123, "AudioVolumeUp", 0xAF),
+    key(125, "NumpadEqual", 0x92),
+    key(127, "Pause", 0x13),
+    key(129, "NumpadComma", 0x6C),
+    key(133, "MetaLeft", 0x5B),
+    key(134, "MetaRight", 0x5C),
+    key(135, "ContextMenu", 0x5D),
+    key(171, "MediaTrackNext", 0xB0),
+    key(172, "MediaPlayPause", 0xB3),
+    key(173, "MediaTrackPrevious", 0xB1),
+    key(174, "MediaStop", 0xB2),
+    key(191, "F13", 0x7C),
+    key(192, "F14", 0x7D),
+    key(193, "F15", 0x7E),
+    key(194, "F16", 0x7F),
+    key(195, "F17", 0x80),
+    key(196, "F18", 0x81),
+    key(197, "F19", 0x82),
+    key(198, "F20", 0x83),
+    key(199, "F21", 0x84),
+    key(200, "F22", 0x85),
+    key(201, "F23", 0x86),
+    key(202, "F24", 0x87),
+];
+
+/// Keypad keys sending navigation keyvals while NumLock is off
+const KEYPAD_NAVIGATION: &[(Key, u32)] = &[
+    (Key::KP_Home, 0x24),
+    (Key::KP_Up, 0x26),
+    (Key::KP_Page_Up, 0x21),
+    (Key::KP_Left, 0x25),
+    (Key::KP_Begin, 0x0C),
+    (Key::KP_Right, 0x27),
+    (Key::KP_End, 0x23),
+    (Key::KP_Down, 0x28),
+    (Key::KP_Page_Down, 0x22),
+    (Key::KP_Insert, 0x2D),
+    (Key::KP_Delete, 0x2E),
+];
+
+/// Modifiers present on both sides of the keyboard, as named in their DOM codes
+const SIDED_MODIFIERS: &[&str] = &["Control", "Shift", "Alt", "Meta"];
+
+fn physical_key(keycode: u32) -> Option<&'static PhysicalKey> {
+    PHYSICAL_KEYS.iter().find(|key| key.keycode == keycode)
+}
+
+/// Windows virtual key code for a key event
+///
+/// Letters and digits follow the active layout through the keyval, like Chromium does,
+/// everything else, and layouts without latin letters, fall back to the physical key.
+pub fn windows_key_code(key: Option<Key>, keycode: u32) -> u32 {
+    if let Some(key) = key {
+        let keyval = key.to_lower().into_glib();
+
+        if (Key::a.into_glib()..=Key::z.into_glib()).contains(&keyval) {
+            return 0x41 + keyval - Key::a.into_glib();
+        }
+
+        if (Key::_0.into_glib()..=Key::_9.into_glib()).contains(&keyval) {
+            return 0x30 + keyval - Key::_0.into_glib();
+        }
+
+        if let Some((_, windows)) = KEYPAD_NAVIGATION.iter().find(|(it, _)| *it == key) {
+            return *windows;
+        }
+    }
+
+    physical_key(keycode).map(|key| key.windows).unwrap_or(0)
+}
+
+pub fn is_keypad(keycode: u32) -> bool {
+    physical_key(keycode).is_some_and(|key| key.code.starts_with("Numpad"))
+}
+
+pub fn is_left(keycode: u32) -> bool {
+    is_sided_modifier(keycode, "Left")
+}
+
+pub fn is_right(keycode: u32) -> bool {
+    is_sided_modifier(keycode, "Right")
+}
+
+fn is_sided_modifier(keycode: u32, side: &str) -> bool {
+    physical_key(keycode).is_some_and(|key| {
+        key.code
+            .strip_suffix(side)
+            .is_some_and(|modifier| SIDED_MODIFIERS.contains(&modifier))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn physical_keys() {
+        for (index, key) in PHYSICAL_KEYS.iter().enumerate() {
+            assert!(
+                PHYSICAL_KEYS[..index]
+                    .iter()
+                    .all(|other| other.keycode != key.keycode),
+                "{} is listed twice",
+                key.keycode
+            );
+
+            let windows = windows_key_code(None, key.keycode);
+            assert_eq!(windows, key.windows, "{}", key.code);
+
+            let expected = if let Some(letter) = key.code.strip_prefix("Key") {
+                letter.as_bytes()[0] as u32
+            } else if let Some(digit) = key.code.strip_prefix("Digit") {
+                0x30 + digit.parse::<u32>().unwrap()
+            } else if let Some(digit) = key.code.strip_prefix("Numpad")
+                && let Ok(digit) = digit.parse::<u32>()
+            {
+                0x60 + digit
+            } else if let Some(number) = key.code.strip_prefix('F')
+                && let Ok(number) = number.parse::<u32>()
+            {
+                0x6F + number
+            } else {
+                continue;
+            };
+
+            assert_eq!(windows, expected, "{}", key.code);
+        }
+    }
+
+    #[test]
+    fn windows_codes() {
+        let cases = [
+            (24, "KeyQ", 0x51),
+            (38, "KeyA", 0x41),
+            (10, "Digit1", 0x31),
+            (19, "Digit0", 0x30),
+            (67, "F1", 0x70),
+            (96, "F12", 0x7B),
+            (202, "F24", 0x87),
+            (20, "Minus", 0xBD),
+            (59, "Comma", 0xBC),
+            (61, "Slash", 0xBF),
+            (49, "Backquote", 0xC0),
+            (118, "Insert", 0x2D),
+            (119, "Delete", 0x2E),
+            (104, "NumpadEnter", 0x0D),
+            (37, "ControlLeft", 0x11),
+            (105, "ControlRight", 0x11),
+            (50, "ShiftLeft", 0x10),
+            (62, "ShiftRight", 0x10),
+            (64, "AltLeft", 0x12),
+            (108, "AltRight", 0x12),
+            (133, "MetaLeft", 0x5B),
+            (134, "MetaRight", 0x5C),
+        ];
+
+        for (keycode, code, windows) in cases {
+            assert_eq!(physical_key(keycode).map(|key| key.code), Some(code));
+            assert_eq!(windows_key_code(None, keycode), windows, "{code}");
+        }
+
+        assert_eq!(windows_key_code(None, 0), 0);
+    }
+
+    #[test]
+    fn layout_keys() {
+        // AZERTY puts A on the physical Q key and digits on shifted keys
+        assert_eq!(windows_key_code(Some(Key::a), 24), 0x41);
+        assert_eq!(windows_key_code(Some(Key::A), 24), 0x41);
+        assert_eq!(windows_key_code(Some(Key::ampersand), 10), 0x31);
+        assert_eq!(windows_key_code(Some(Key::_1), 10), 0x31);
+        // Non latin layouts fall back to the physical key
+        assert_eq!(windows_key_code(Some(Key::Cyrillic_ef), 38), 0x41);
+    }
+
+    #[test]
+    fn numpad_keys() {
+        // NumLock on
+        assert_eq!(windows_key_code(Some(Key::KP_7), 79), 0x67);
+        assert_eq!(windows_key_code(Some(Key::KP_0), 90), 0x60);
+        assert_eq!(windows_key_code(Some(Key::KP_Decimal), 91), 0x6E);
+        // NumLock off
+        assert_eq!(windows_key_code(Some(Key::KP_Home), 79), 0x24);
+        assert_eq!(windows_key_code(Some(Key::KP_Insert), 90), 0x2D);
+        assert_eq!(windows_key_code(Some(Key::KP_Delete), 91), 0x2E);
+        assert_eq!(windows_key_code(Some(Key::KP_Begin), 84), 0x0C);
+        // Not affected by NumLock
+        assert_eq!(windows_key_code(Some(Key::KP_Add), 86), 0x6B);
+        assert_eq!(windows_key_code(Some(Key::KP_Enter), 104), 0x0D);
+    }
+
+    #[test]
+    fn key_locations() {
+        for keycode in [63, 79, 90, 91, 104, 106, 125, 129] {
+            assert!(is_keypad(keycode), "{keycode}");
+        }
+        for keycode in [10, 36, 77, 118, 119] {
+            assert!(!is_keypad(keycode), "{keycode}");
+        }
+
+        for keycode in [37, 50, 64, 133] {
+            assert!(is_left(keycode) && !is_right(keycode), "{keycode}");
+        }
+        for keycode in [62, 105, 108, 134] {
+            assert!(is_right(keycode) && !is_left(keycode), "{keycode}");
+        }
+        // Named after a side but not modifiers
+        for keycode in [34, 35, 113, 114] {
+            assert!(!is_left(keycode) && !is_right(keycode), "{keycode}");
+        }
+    }
+}
@@ -1,5 +1,7 @@
 pub mod ipc;
+pub mod keyboard;
 pub mod pbo_manager;
+pub mod proxy;
 pub mod states;
 pub mod types;
 
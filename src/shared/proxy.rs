@@ -0,0 +1,152 @@
+use std::time::Duration;
+
+use ashpd::desktop::proxy_resolver::ProxyResolver;
+use percent_encoding::percent_decode_str;
+use tracing::{debug, error, warn};
+use url::Url;
+
+use crate::{
+    config::STARTUP_URL,
+    settings::{ProxyMode, ProxySettings},
+};
+
+/// How long startup waits for the proxy resolver portal
+const PORTAL_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Proxy configuration shared by the browser, the metadata requests and the server
+#[derive(Debug, Clone, Default)]
+pub enum Proxy {
+    /// Leave proxy handling to the system configuration
+    #[default]
+    System,
+    /// Connect directly
+    Direct,
+    /// Send every request through a HTTP or SOCKS5 proxy
+    Server(Url),
+    /// Let a PAC script pick the proxy
+    Pac(Url),
+}
+
+impl Proxy {
+    pub async fn resolve(settings: &ProxySettings) -> Self {
+        match settings.mode {
+            ProxyMode::System => match tokio::time::timeout(PORTAL_TIMEOUT, Self::lookup()).await {
+                Ok(proxy) => proxy.unwrap_or_default(),
+                Err(_) => {
+                    warn!("The proxy resolver did not answer, using the proxy environment");
+                    Self::System
+                }
+            },
+            ProxyMode::Direct => Self::Direct,
+            ProxyMode::Manual => match parse_url(&settings.url) {
+                Some(url) => Self::Server(url),
+                None => {
+                    error!(
+                        "Invalid proxy url {:?}, using the system proxy instead",
+                        settings.url
+                    );
+                    Self::System
+                }
+            },
+            ProxyMode::Pac => match parse_url(&settings.pac_url) {
+                Some(url) => Self::Pac(url),
+                None => {
+                    error!(
+                        "Invalid PAC url {:?}, using the system proxy instead",
+                        settings.pac_url
+                    );
+                    Self::System
+                }
+            },
+        }
+    }
+
+    /// Username and password of a proxy server, decoded from its url
+    pub fn credentials(&self) -> Option<(String, String)> {
+        let Self::Server(url) = self else {
+            return None;
+        };
+
+        if url.username().is_empty() {
+            return None;
+        }
+
+        let decode = |value| percent_decode_str(value).decode_utf8_lossy().into_owned();
+        let password = url.password().unwrap_or_default();
+
+        Some((decode(url.username()), decode(password)))
+    }
+
+    /// Asks the proxy resolver portal which proxy to use for the web UI
+    async fn lookup() -> Option<Self> {
+        let uri = Url::parse(STARTUP_URL).ok()?;
+
+        let resolver = ProxyResolver::new()
+            .await
+            .map_err(|e| warn!("Failed to connect to the proxy resolver: {e}"))
+            .ok()?;
+
+        let proxies = resolver
+            .lookup(&uri)
+            .await
+            .map_err(|e| warn!("Failed to resolve the system proxy: {e}"))
+            .ok()?;
+
+        debug!("System proxies: {proxies:?}");
+
+        proxies
+            .into_iter()
+            .next()
+            .map(|proxy| match proxy.scheme() {
+                "direct" => Self::Direct,
+                _ => Self::Server(proxy),
+            })
+    }
+
+    /// Environment variables understood by most HTTP clients,
+    /// `None` values are removed from the environment
+    pub fn env(&self) -> Vec<(&'static str, Option<String>)> {
+        let (proxy, no_proxy) = match self {
+            Self::Server(url) => (Some(url.to_string()), None),
+            Self::Direct => (None, Some("*".to_owned())),
+            // Clients keep the inherited environment, PAC scripts are not supported
+            Self::System | Self::Pac(_) => return Vec::new(),
+        };
+
+        [
+            "http_proxy",
+            "https_proxy",
+            "all_proxy",
+            "HTTP_PROXY",
+            "HTTPS_PROXY",
+            "ALL_PROXY",
+        ]
+        .into_iter()
+        .map(|name| (name, proxy.clone()))
+        .chain(
+            ["no_proxy", "NO_PROXY"]
+                .into_iter()
+                .map(|name| (name, no_proxy.clone())),
+        )
+        .collect()
+    }
+
+    /// Blocking HTTP client going through the proxy, never connecting directly instead
+    pub fn http_client(&self) -> reqwest::Result<reqwest::blocking::Client> {
+        let builder = reqwest::blocking::Client::builder();
+
+        let builder = match self {
+            Self::Direct => builder.no_proxy(),
+            Self::Server(url) => builder.proxy(reqwest::Proxy::all(url.as_str())?),
+            // Uses the proxy environment variables
+            Self::System | Self::Pac(_) => builder,
+        };
+
+        builder.build()
+    }
+}
+
+/// Parses a proxy or PAC url, which needs a host to be usable
+pub fn parse_url(url: &str) -> Option<Url> {
+    Url::parse(url.trim()).ok().filter(|url| url.has_host())
+}
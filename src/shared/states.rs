@@ -1,17 +1,22 @@
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 
 use cef::sys::{
     cef_event_flags_t as EventFlags, cef_key_event_type_t::KEYEVENT_KEYDOWN,
-    cef_key_event_type_t::KEYEVENT_KEYUP,
+    cef_key_event_type_t::KEYEVENT_KEYUP, cef_pointer_type_t::CEF_POINTER_TYPE_TOUCH,
+    cef_touch_event_type_t::CEF_TET_CANCELLED, cef_touch_event_type_t::CEF_TET_MOVED,
+    cef_touch_event_type_t::CEF_TET_PRESSED, cef_touch_event_type_t::CEF_TET_RELEASED,
 };
-use cef::{KeyEvent, MouseEvent};
-use gtk::gdk::ModifierType;
+use cef::{KeyEvent, MouseEvent, TouchEvent};
+use gtk::gdk::{Key, ModifierType};
+
+use crate::shared::keyboard;
 
 #[derive(Default, Debug)]
 pub struct PointerState {
     position: Cell<(f64, f64)>,
     pressed: Cell<bool>,
     button: Cell<u32>,
+    modifiers: Cell<ModifierType>,
     over: Cell<bool>,
 }
 
@@ -40,6 +45,15 @@ impl PointerState {
         self.button.set(r#type);
     }
 
+    /// Keyboard modifiers and mouse buttons held during the last event
+    pub fn modifiers(&self) -> ModifierType {
+        self.modifiers.get()
+    }
+
+    pub fn set_modifiers(&self, modifiers: ModifierType) {
+        self.modifiers.set(modifiers);
+    }
+
     pub fn over(&self) -> bool {
         self.over.get()
     }
@@ -54,116 +68,129 @@ impl From<&PointerState> for MouseEvent {
         let (x, y) = pointer_state.position();
         let pressed = pointer_state.pressed();
         let button = pointer_state.button();
+        let state = pointer_state.modifiers();
+
+        let mut modifiers = EventFlags::EVENTFLAG_NONE.0;
+
+        if state.contains(ModifierType::SHIFT_MASK) {
+            modifiers |= EventFlags::EVENTFLAG_SHIFT_DOWN.0;
+        }
+
+        if state.contains(ModifierType::CONTROL_MASK) {
+            modifiers |= EventFlags::EVENTFLAG_CONTROL_DOWN.0;
+        }
+
+        if state.contains(ModifierType::ALT_MASK) {
+            modifiers |= EventFlags::EVENTFLAG_ALT_DOWN.0;
+        }
+
+        if state.intersects(ModifierType::SUPER_MASK | ModifierType::META_MASK) {
+            modifiers |= EventFlags::EVENTFLAG_COMMAND_DOWN.0;
+        }
+
+        if state.contains(ModifierType::LOCK_MASK) {
+            modifiers |= EventFlags::EVENTFLAG_CAPS_LOCK_ON.0;
+        }
+
+        // The state of a press event does not contain the button being pressed yet
+        if state.contains(ModifierType::BUTTON1_MASK) || (pressed && button == 1) {
+            modifiers |= EventFlags::EVENTFLAG_LEFT_MOUSE_BUTTON.0;
+        }
+
+        if state.contains(ModifierType::BUTTON2_MASK) || (pressed && button == 2) {
+            modifiers |= EventFlags::EVENTFLAG_MIDDLE_MOUSE_BUTTON.0;
+        }
+
+        if state.contains(ModifierType::BUTTON3_MASK) || (pressed && button == 3) {
+            modifiers |= EventFlags::EVENTFLAG_RIGHT_MOUSE_BUTTON.0;
+        }
 
         MouseEvent {
             x: x as i32,
             y: y as i32,
-            modifiers: match button {
-                1 if pressed => EventFlags::EVENTFLAG_LEFT_MOUSE_BUTTON.0,
-                3 if pressed => EventFlags::EVENTFLAG_MIDDLE_MOUSE_BUTTON.0,
-                2 if pressed => EventFlags::EVENTFLAG_RIGHT_MOUSE_BUTTON.0,
-                _ => 0,
-            },
+            modifiers,
         }
     }
 }
 
-enum KeyCode {
-    None,
-    Backspace,
-    Tab,
-    Enter,
-    Control,
-    Escape,
-    Space,
-    PageUp,
-    PageDown,
-    End,
-    Home,
-    ArrowLeft,
-    ArrowUp,
-    ArrowRight,
-    ArrowDown,
-    Equal,
-    Minus,
-    A,
-    C,
-    V,
-    X,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchPhase {
+    Pressed,
+    Moved,
+    Released,
+    Cancelled,
 }
 
-impl KeyCode {
-    fn windows(&self) -> u32 {
-        match self {
-            KeyCode::Backspace => 8,
-            KeyCode::Tab => 9,
-            KeyCode::Enter => 13,
-            KeyCode::Control => 17,
-            KeyCode::Escape => 27,
-            KeyCode::Space => 32,
-            KeyCode::PageUp => 33,
-            KeyCode::PageDown => 34,
-            KeyCode::End => 35,
-            KeyCode::Home => 36,
-            KeyCode::ArrowLeft => 37,
-            KeyCode::ArrowUp => 38,
-            KeyCode::ArrowRight => 39,
-            KeyCode::ArrowDown => 40,
-            KeyCode::Equal => 61,
-            KeyCode::A => 65,
-            KeyCode::C => 67,
-            KeyCode::V => 86,
-            KeyCode::X => 88,
-            KeyCode::Minus => 173,
-            KeyCode::None => 0,
-        }
-    }
+/// A single touch point of a touchscreen, `id` stays the same for the whole sequence
+#[derive(Debug, Clone, Copy)]
+pub struct TouchPoint {
+    pub id: i32,
+    pub x: f64,
+    pub y: f64,
+    pub phase: TouchPhase,
 }
 
-impl From<u32> for KeyCode {
-    fn from(value: u32) -> Self {
-        match value {
-            22 => KeyCode::Backspace,
-            23 => KeyCode::Tab,
-            36 => KeyCode::Enter,
-            37 => KeyCode::Control,
-            9 => KeyCode::Escape,
-            65 => KeyCode::Space,
-            112 => KeyCode::PageUp,
-            117 => KeyCode::PageDown,
-            115 => KeyCode::End,
-            110 => KeyCode::Home,
-            113 => KeyCode::ArrowLeft,
-            111 => KeyCode::ArrowUp,
-            114 => KeyCode::ArrowRight,
-            116 => KeyCode::ArrowDown,
-            21 => KeyCode::Equal,
-            38 => KeyCode::A,
-            54 => KeyCode::C,
-            55 => KeyCode::V,
-            53 => KeyCode::X,
-            20 => KeyCode::Minus,
-            _ => KeyCode::None,
+impl From<&TouchPoint> for TouchEvent {
+    fn from(touch_point: &TouchPoint) -> Self {
+        let event_type = match touch_point.phase {
+            TouchPhase::Pressed => CEF_TET_PRESSED,
+            TouchPhase::Moved => CEF_TET_MOVED,
+            TouchPhase::Released => CEF_TET_RELEASED,
+            TouchPhase::Cancelled => CEF_TET_CANCELLED,
+        };
+
+        TouchEvent {
+            id: touch_point.id,
+            x: touch_point.x as f32,
+            y: touch_point.y as f32,
+            pressure: 1.0,
+            type_: event_type.into(),
+            pointer_type: CEF_POINTER_TYPE_TOUCH.into(),
+            ..Default::default()
         }
     }
 }
 
 #[derive(Default, Debug)]
 pub struct KeyboardState {
-    character: Cell<Option<char>>,
+    key: Cell<Option<Key>>,
+    text: RefCell<Option<String>>,
+    composing: Cell<bool>,
     pressed: Cell<bool>,
     code: Cell<u32>,
     control_modifier: Cell<bool>,
     shift_modifier: Cell<bool>,
+    alt_modifier: Cell<bool>,
+    meta_modifier: Cell<bool>,
+    caps_lock: Cell<bool>,
+    num_lock: Cell<bool>,
 }
 
 impl KeyboardState {
-    pub fn character(&self) -> Option<char> {
-        self.character.get()
+    pub fn key(&self) -> Option<Key> {
+        self.key.get()
     }
 
-    pub fn set_character(&self, character: Option<char>) {
-        self.character.set(character);
+    pub fn set_key(&self, key: Key) {
+        self.key.set(Some(key));
+    }
+
+    /// Text produced by the key, either directly or committed by the input method
+    pub fn text(&self) -> Option<String> {
+        self.text.borrow().clone()
+    }
+
+    pub fn set_text(&self, text: Option<String>) {
+        self.text.replace(text);
+    }
+
+    /// Whether the key was consumed by an input method composition
+    pub fn composing(&self) -> bool {
+        self.composing.get()
+    }
+
+    pub fn set_composing(&self, composing: bool) {
+        self.composing.set(composing);
     }
 
     pub fn pressed(&self) -> bool {
@@ -179,9 +206,11 @@ impl KeyboardState {
     }
 
     pub fn windows_code(&self) -> u32 {
-        let code = self.code.get();
-        let native_code = KeyCode::from(code);
-        native_code.windows()
+        if self.composing() {
+            return keyboard::PROCESS_KEY;
+        }
+
+        keyboard::windows_key_code(self.key(), self.code())
     }
 
     pub fn set_code(&self, code: u32) {
@@ -196,12 +225,35 @@ impl KeyboardState {
         self.shift_modifier.get()
     }
 
+    pub fn alt_modifier(&self) -> bool {
+        self.alt_modifier.get()
+    }
+
+    pub fn meta_modifier(&self) -> bool {
+        self.meta_modifier.get()
+    }
+
     pub fn set_modifiers(&self, modifiers: ModifierType) {
         let control_modifier = modifiers.contains(ModifierType::CONTROL_MASK);
         self.control_modifier.set(control_modifier);
 
         let shift_modifier = modifiers.contains(ModifierType::SHIFT_MASK);
         self.shift_modifier.set(shift_modifier);
+
+        let alt_modifier = modifiers.contains(ModifierType::ALT_MASK);
+        self.alt_modifier.set(alt_modifier);
+
+        let meta_modifier =
+            modifiers.intersects(ModifierType::META_MASK | ModifierType::SUPER_MASK);
+        self.meta_modifier.set(meta_modifier);
+
+        let caps_lock = modifiers.contains(ModifierType::LOCK_MASK);
+        self.caps_lock.set(caps_lock);
+    }
+
+    /// GDK has no modifier for NumLock, it comes from the keyboard device
+    pub fn set_num_lock(&self, num_lock: bool) {
+        self.num_lock.set(num_lock);
     }
 }
 
@@ -226,6 +278,34 @@ impl From<&KeyboardState> for KeyEvent {
             modifiers |= EventFlags::EVENTFLAG_CONTROL_DOWN.0;
         }
 
+        if keyboard_state.alt_modifier() {
+            modifiers |= EventFlags::EVENTFLAG_ALT_DOWN.0;
+        }
+
+        if keyboard_state.meta_modifier() {
+            modifiers |= EventFlags::EVENTFLAG_COMMAND_DOWN.0;
+        }
+
+        if keyboard_state.caps_lock.get() {
+            modifiers |= EventFlags::EVENTFLAG_CAPS_LOCK_ON.0;
+        }
+
+        if keyboard_state.num_lock.get() {
+            modifiers |= EventFlags::EVENTFLAG_NUM_LOCK_ON.0;
+        }
+
+        if keyboard::is_keypad(code) {
+            modifiers |= EventFlags::EVENTFLAG_IS_KEY_PAD.0;
+        }
+
+        if keyboard::is_left(code) {
+            modifiers |= EventFlags::EVENTFLAG_IS_LEFT.0;
+        }
+
+        if keyboard::is_right(code) {
+            modifiers |= EventFlags::EVENTFLAG_IS_RIGHT.0;
+        }
+
         KeyEvent {
             type_: event_type,
             native_key_code: code as i32,
@@ -1,8 +1,70 @@
 use std::sync::atomic::AtomicU64;
 
+use gtk::glib;
+use serde::{Deserialize, Serialize};
+
 pub static SCALE_FACTOR: AtomicU64 = AtomicU64::new(0);
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackStatus {
+    #[default]
+    Stopped,
+    Playing,
+    Paused,
+}
+
+/// Cursor requested by the page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BrowserCursor {
+    /// CSS cursor name
+    Named(&'static str),
+    /// Premultiplied BGRA bitmap
+    Custom {
+        width: i32,
+        height: i32,
+        hotspot_x: i32,
+        hotspot_y: i32,
+        pixels: Vec<u8>,
+    },
+    Hidden,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardAction {
+    Copy,
+    Cut,
+    Paste,
+    PasteAndMatchStyle,
+}
+
+/// Capability a page asks for, decoded from the CEF permission bits
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, glib::Boxed,
+)]
+#[boxed_type(name = "Permission")]
+#[serde(rename_all = "kebab-case")]
+pub enum Permission {
+    Camera,
+    Microphone,
+    ScreenCapture,
+    Notifications,
+    Clipboard,
+    Geolocation,
+    MultipleDownloads,
+    LocalFonts,
+    PointerLock,
+    KeyboardLock,
+    IdleDetection,
+    WindowManagement,
+    StorageAccess,
+    ProtectedMedia,
+    LocalNetworkAccess,
+    /// Any permission without a dedicated variant, never remembered
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, glib::Boxed)]
+#[boxed_type(name = "MprisCommand")]
 pub enum MprisCommand {
     Play,
     Pause,
@@ -13,6 +75,9 @@ pub enum MprisCommand {
     Seek(i64),
     SetPosition(i64),
     SetRate(f64),
+    SetVolume(f64),
+    AdjustVolume(f64),
+    ToggleMute,
 }
 
 #[derive(Debug, Clone)]
@@ -21,11 +86,17 @@ pub enum UserEvent {
     Quit,
 
     MprisCommand(MprisCommand),
+    /// Whether the web app has a video to play after the current one
+    NextVideo(bool),
     MetadataUpdate {
         title: Option<String>,
         artist: Option<String>,
         poster: Option<String>,
         thumbnail: Option<String>,
         logo: Option<String>,
+        id: Option<String>,
+        kind: Option<String>,
+        /// Unknown until the genres of the item are
+        adult: Option<bool>,
     },
 }